@@ -0,0 +1,594 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+        variable::{Type, Variable},
+    },
+    scope::Scope,
+    utils::{input_error, sorted_subfolders},
+};
+
+/// Variable written by a 'declare', 'let' or 'input' command
+#[derive(Debug, Clone, Copy)]
+pub struct Binding {
+    pub index: usize,
+    pub var_type: Type,
+
+    /// the command introduces the variable in its own scope
+    pub declares: bool,
+}
+
+/// Types of a procedure, inferred from its first call
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub index: usize,
+    pub parameters: Vec<Type>,
+
+    /// type of the values returned, if any
+    pub return_type: Option<Type>,
+}
+
+/// Static scope analysis of a program.
+///
+/// Follows the interpreter rules : every If/While body runs in a child scope,
+/// 'let' and 'input' write to a variable visible from an enclosing scope and
+/// declare it locally otherwise. Scopes only hold typed null variables here.
+///
+/// Procedure bodies are analysed at their first call, in a scope whose parent is
+/// the global scope. Their uses of global variables are recorded : they run but
+/// can not be transpiled to separate functions.
+#[derive(Default)]
+pub struct ScopeAnalysis {
+    /// bindings indexed by command folder
+    bindings: HashMap<String, Binding>,
+
+    /// static types indexed by expression folder
+    types: HashMap<String, Type>,
+
+    /// 'define' command folder and parameter count indexed by procedure name
+    procedures: HashMap<usize, (String, usize)>,
+
+    /// signatures indexed by 'define' command folder
+    signatures: HashMap<String, Signature>,
+
+    /// 'define' command folders of the procedures being analysed, innermost last
+    calls: Vec<String>,
+
+    global_scope: Option<Rc<RefCell<Scope>>>,
+
+    /// folder and variable index of the global variables used by procedures
+    global_uses: Vec<(String, usize)>,
+
+    context: Rc<Context>,
+}
+
+impl ScopeAnalysis {
+    pub fn new(folder: &str, context: &Rc<Context>) -> std::io::Result<Self> {
+        let mut analysis = ScopeAnalysis {
+            context: context.clone(),
+            ..Default::default()
+        };
+        let global_scope = Rc::new(RefCell::new(Scope::new(None)));
+        analysis.global_scope = Some(global_scope.clone());
+
+        analysis.analyse_block(folder, &global_scope)?;
+
+        Ok(analysis)
+    }
+
+    pub fn binding(&self, folder: &str) -> std::io::Result<Binding> {
+        self.bindings
+            .get(folder)
+            .copied()
+            .ok_or_else(|| input_error(format!("{folder} : command has not been analysed")))
+    }
+
+    pub fn type_of(&self, folder: &str) -> std::io::Result<Type> {
+        self.types
+            .get(folder)
+            .copied()
+            .ok_or_else(|| input_error(format!("{folder} : expression has not been analysed")))
+    }
+
+    /// Signature of the procedure defined by a 'define' command, None if it is never called
+    pub fn signature(&self, folder: &str) -> Option<&Signature> {
+        self.signatures.get(folder)
+    }
+
+    /// First use of a global variable by a procedure : its folder and the variable index
+    pub fn global_use(&self) -> Option<(&str, usize)> {
+        self.global_uses
+            .first()
+            .map(|(folder, index)| (folder.as_str(), *index))
+    }
+
+    /// 'define' command folders of the procedures called, sorted by name
+    pub fn called_procedures(&self) -> Vec<&str> {
+        let mut procedures: Vec<(&str, usize)> = self
+            .signatures
+            .iter()
+            .map(|(folder, signature)| (folder.as_str(), signature.index))
+            .collect();
+        procedures.sort_by_key(|(_, index)| *index);
+
+        procedures.into_iter().map(|(folder, _)| folder).collect()
+    }
+
+    fn analyse_block(&mut self, folder: &str, scope: &Rc<RefCell<Scope>>) -> std::io::Result<()> {
+        for folder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&folder, scope, &self.context)?;
+            if self.context.optimizations.is_dead(cmd.folder()) {
+                continue;
+            }
+
+            self.analyse_command(&cmd, scope)?;
+        }
+
+        Ok(())
+    }
+
+    fn analyse_command(
+        &mut self,
+        cmd: &Command,
+        scope: &Rc<RefCell<Scope>>,
+    ) -> std::io::Result<()> {
+        match cmd.command_type {
+            CommandType::Declare => {
+                let index = cmd.variable_index()?;
+                let var_type = cmd.declared_type()?;
+
+                scope
+                    .borrow_mut()
+                    .declare_variable_with_type(var_type, index)?;
+
+                self.bind(cmd, index, var_type, true);
+            }
+
+            CommandType::Let => {
+                let index = cmd.variable_index()?;
+                let var_type = self.analyse_expression(&cmd.expression()?, scope)?;
+
+                self.assign(cmd, index, var_type, scope)?;
+            }
+
+            CommandType::Input => {
+                let index = cmd.variable_index()?;
+
+                // input into a fresh variable is read as a string
+                let var_type = match scope.borrow().get_variable(index) {
+                    Some(var) => var.get_type(),
+                    None => Type::String,
+                };
+
+                if let Type::Array(_) | Type::Bool = var_type {
+                    return Err(input_error(format!(
+                        "{} : input can not be read into {var_type:?}",
+                        cmd.folder()
+                    )));
+                }
+
+                self.assign(cmd, index, var_type, scope)?;
+            }
+
+            CommandType::Print => {
+                self.analyse_expression(&cmd.expression()?, scope)?;
+            }
+
+            CommandType::Define => {
+                let index = cmd.procedure_index()?;
+
+                if let Some((folder, _)) = self.procedures.get(&index) {
+                    if folder != cmd.folder() {
+                        return Err(input_error(format!(
+                            "{} : proc_{index} is already defined in {folder}",
+                            cmd.folder()
+                        )));
+                    }
+                }
+
+                self.procedures
+                    .insert(index, (cmd.folder().to_owned(), cmd.parameter_count()?));
+            }
+
+            CommandType::Call => {
+                self.analyse_call(
+                    cmd.folder(),
+                    cmd.procedure_index()?,
+                    cmd.arguments_folder()?,
+                    scope,
+                )?;
+            }
+
+            CommandType::Return => {
+                let return_type = self.analyse_expression(&cmd.expression()?, scope)?;
+
+                let signature = match self.calls.last() {
+                    Some(folder) => self.signatures.get_mut(folder).unwrap(),
+                    None => {
+                        return Err(input_error(format!(
+                            "{} : 'return' outside of a procedure",
+                            cmd.folder()
+                        )))
+                    }
+                };
+
+                match signature.return_type {
+                    Some(previous) if previous != return_type => {
+                        return Err(input_error(format!(
+                            "{} : proc_{} returns both {previous:?} and {return_type:?}",
+                            cmd.folder(),
+                            signature.index
+                        )));
+                    }
+                    _ => signature.return_type = Some(return_type),
+                }
+            }
+
+            CommandType::Write | CommandType::CreateFolder | CommandType::RemoveFolder => {
+                let path = cmd.expression()?;
+                let path_type = self.analyse_expression(&path, scope)?;
+                if path_type != Type::String {
+                    return Err(input_error(format!(
+                        "{} : expected a path as String, found {path_type:?}",
+                        path.folder()
+                    )));
+                }
+
+                if cmd.command_type == CommandType::Write {
+                    self.analyse_expression(&cmd.value_expression()?, scope)?;
+                }
+            }
+
+            CommandType::If | CommandType::While => {
+                self.analyse_expression(&cmd.expression()?, scope)?;
+                let condition = self.context.optimizations.constant_condition(cmd.folder());
+
+                if condition != Some(false) {
+                    let body_scope = Rc::new(RefCell::new(Scope::new(Some(scope.clone()))));
+                    self.analyse_block(cmd.body_folder()?, &body_scope)?;
+                }
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    if condition != Some(true) {
+                        let else_scope = Rc::new(RefCell::new(Scope::new(Some(scope.clone()))));
+                        self.analyse_block(else_folder, &else_scope)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Types of the arguments are bound to the procedure on its first call
+    fn analyse_call(
+        &mut self,
+        folder: &str,
+        index: usize,
+        arguments_folder: &str,
+        scope: &Rc<RefCell<Scope>>,
+    ) -> std::io::Result<Option<Type>> {
+        let (define_folder, parameters) =
+            self.procedures.get(&index).cloned().ok_or_else(|| {
+                input_error(format!("{folder} : procedure proc_{index} is not defined"))
+            })?;
+
+        let mut arguments = vec![];
+        for argument in sorted_subfolders(arguments_folder)? {
+            let exp = Expression::new(&argument, scope, &self.context)?;
+            arguments.push(self.analyse_expression(&exp, scope)?);
+        }
+
+        if arguments.len() != parameters {
+            return Err(input_error(format!(
+                "{folder} : proc_{index} expects {parameters} arguments, {} found",
+                arguments.len()
+            )));
+        }
+
+        if let Some(signature) = self.signatures.get(&define_folder) {
+            if signature.parameters != arguments {
+                return Err(input_error(format!(
+                    "{folder} : proc_{index} is called with {arguments:?} but expects {:?}",
+                    signature.parameters
+                )));
+            }
+
+            return Ok(signature.return_type);
+        }
+
+        self.signatures.insert(
+            define_folder.clone(),
+            Signature {
+                index,
+                parameters: arguments.clone(),
+                return_type: None,
+            },
+        );
+
+        let procedure_scope = Rc::new(RefCell::new(Scope::new(self.global_scope.clone())));
+        for (i, argument) in arguments.into_iter().enumerate() {
+            procedure_scope
+                .borrow_mut()
+                .declare_variable_with_type(argument, i)?;
+        }
+
+        let body = Command::new(&define_folder, scope, &self.context)?
+            .body_folder()?
+            .to_owned();
+
+        self.calls.push(define_folder.clone());
+        let result = self.analyse_block(&body, &procedure_scope);
+        self.calls.pop();
+        result?;
+
+        Ok(self.signatures[&define_folder].return_type)
+    }
+
+    /// Variable `index` of `scope` is a global variable used by the procedure being analysed
+    fn is_global(&self, scope: &Rc<RefCell<Scope>>, index: usize) -> bool {
+        let Some(global_scope) = &self.global_scope else {
+            return false;
+        };
+        if self.calls.is_empty() {
+            return false;
+        }
+
+        let mut scope = scope.clone();
+        loop {
+            let declared = scope.borrow().variables().contains_key(&index);
+            if Rc::ptr_eq(&scope, global_scope) {
+                return declared;
+            }
+            if declared {
+                return false;
+            }
+
+            let parent = scope.borrow().parent();
+            match parent {
+                Some(parent) => scope = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn assign(
+        &mut self,
+        cmd: &Command,
+        index: usize,
+        var_type: Type,
+        scope: &Rc<RefCell<Scope>>,
+    ) -> std::io::Result<()> {
+        if self.is_global(scope, index) {
+            self.global_uses.push((cmd.folder().to_owned(), index));
+        }
+
+        let mut scope = scope.borrow_mut();
+        let declares = scope.get_variable(index).is_none();
+
+        scope
+            .set_or_create_variable(index, Variable::from(var_type))
+            .map_err(|err| input_error(format!("{} : {err}", cmd.folder())))?;
+
+        self.bind(cmd, index, var_type, declares);
+
+        Ok(())
+    }
+
+    fn bind(&mut self, cmd: &Command, index: usize, var_type: Type, declares: bool) {
+        self.bindings.insert(
+            cmd.folder().to_owned(),
+            Binding {
+                index,
+                var_type,
+                declares,
+            },
+        );
+    }
+
+    fn analyse_expression(
+        &mut self,
+        exp: &Expression,
+        scope: &Rc<RefCell<Scope>>,
+    ) -> std::io::Result<Type> {
+        let exp_type = match exp.expression_type {
+            ExpressionType::Variable => {
+                let index = exp.variable_index()?;
+
+                if self.is_global(scope, index) {
+                    self.global_uses.push((exp.folder().to_owned(), index));
+                }
+
+                match scope.borrow().get_variable(index) {
+                    Some(var) => var.get_type(),
+                    None => {
+                        return Err(input_error(format!(
+                            "{} : expression error, variable var_{index} does not exist",
+                            exp.folder()
+                        )))
+                    }
+                }
+            }
+
+            ExpressionType::LiteralValue => exp.get_literal_value()?.get_type(),
+
+            ExpressionType::Not => {
+                self.analyse_expression(&exp.operand()?, scope)?;
+                self.context.truth_type()
+            }
+
+            ExpressionType::Cast => {
+                let from = self.analyse_expression(&exp.cast_operand()?, scope)?;
+                let to = exp.target_type()?;
+
+                if !can_cast(from, to) {
+                    return Err(input_error(format!(
+                        "{} : can not cast {from:?} to {to:?}",
+                        exp.folder()
+                    )));
+                }
+                to
+            }
+
+            ExpressionType::Call => {
+                let index = exp.procedure_index()?;
+                let return_type =
+                    self.analyse_call(exp.folder(), index, exp.arguments_folder(), scope)?;
+
+                match return_type {
+                    Some(return_type) => return_type,
+                    None => {
+                        let recursive = self
+                            .procedures
+                            .get(&index)
+                            .is_some_and(|(folder, _)| self.calls.contains(folder));
+
+                        return Err(input_error(if recursive {
+                            format!(
+                                "{} : proc_{index} is called before it returns a value, its return type can not be inferred",
+                                exp.folder()
+                            )
+                        } else {
+                            format!("{} : proc_{index} does not return a value", exp.folder())
+                        }));
+                    }
+                }
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                let operands = exp.sequence_operands()?;
+                let types = operands
+                    .iter()
+                    .map(|operand| self.analyse_expression(operand, scope))
+                    .collect::<std::io::Result<Vec<Type>>>()?;
+
+                match sequence_type(exp_type, &types) {
+                    Some(exp_type) => exp_type,
+                    None => {
+                        return Err(input_error(format!(
+                            "{} : can not apply {exp_type:?} to {types:?}",
+                            exp.folder()
+                        )))
+                    }
+                }
+            }
+
+            _ => {
+                let (first, second) = exp.operands()?;
+                let a = self.analyse_expression(&first, scope)?;
+                let b = self.analyse_expression(&second, scope)?;
+
+                match binary_type(exp.expression_type, a, b, &self.context) {
+                    Some(exp_type) => exp_type,
+                    None => {
+                        return Err(input_error(format!(
+                            "{} : can not apply {:?} to {a:?} and {b:?}",
+                            exp.folder(),
+                            exp.expression_type
+                        )))
+                    }
+                }
+            }
+        };
+
+        self.types.insert(exp.folder().to_owned(), exp_type);
+
+        Ok(exp_type)
+    }
+}
+
+/// Type produced by the `Variable` operators, None if the operation panics
+pub fn binary_type(exp_type: ExpressionType, a: Type, b: Type, context: &Context) -> Option<Type> {
+    let numeric = |t: Type| t == Type::Int || t == Type::Float;
+
+    match exp_type {
+        // Ints are promoted when compared to Floats, other types only compare to themselves
+        ExpressionType::EqualTo
+        | ExpressionType::GreaterThan
+        | ExpressionType::LessThan
+        | ExpressionType::NotEqualTo
+            if a == b || (numeric(a) && numeric(b)) =>
+        {
+            Some(context.truth_type())
+        }
+
+        ExpressionType::And | ExpressionType::Or => Some(context.truth_type()),
+
+        ExpressionType::BitAnd
+        | ExpressionType::BitOr
+        | ExpressionType::BitXor
+        | ExpressionType::ShiftLeft
+        | ExpressionType::ShiftRight
+        | ExpressionType::IntDivide
+            if a == Type::Int && b == Type::Int =>
+        {
+            Some(Type::Int)
+        }
+
+        // Chars are concatenated to Strings and to other Chars
+        ExpressionType::Add
+            if matches!(a, Type::String | Type::Char) && matches!(b, Type::String | Type::Char) =>
+        {
+            Some(Type::String)
+        }
+
+        ExpressionType::Divide if numeric(a) && numeric(b) => Some(Type::Float),
+
+        ExpressionType::Add
+        | ExpressionType::Substract
+        | ExpressionType::Multiply
+        | ExpressionType::Modulo
+            if numeric(a) && numeric(b) =>
+        {
+            if a == Type::Int && b == Type::Int {
+                Some(Type::Int)
+            } else {
+                Some(Type::Float)
+            }
+        }
+
+        _ => None,
+    }
+}
+
+/// Type produced by an array or string expression, `types` holding the type of the sequence first
+pub fn sequence_type(exp_type: ExpressionType, types: &[Type]) -> Option<Type> {
+    if types[0] == Type::String {
+        return match (exp_type, &types[1..]) {
+            (ExpressionType::Length, []) => Some(Type::Int),
+            (ExpressionType::IndexGet, [Type::Int]) => Some(Type::Char),
+            (ExpressionType::Substring, [Type::Int, Type::Int]) => Some(Type::String),
+            _ => None,
+        };
+    }
+
+    let element = match types[0] {
+        Type::Array(element) => *element,
+        _ => return None,
+    };
+
+    match (exp_type, &types[1..]) {
+        (ExpressionType::Length, []) => Some(Type::Int),
+        (ExpressionType::IndexGet, [Type::Int]) => Some(element),
+        (ExpressionType::IndexSet, [Type::Int, value]) if *value == element => Some(types[0]),
+        (ExpressionType::Push, [value]) if *value == element => Some(types[0]),
+        _ => None,
+    }
+}
+
+/// Conversions allowed by cast expressions, they may still fail at runtime
+pub fn can_cast(from: Type, to: Type) -> bool {
+    match (from, to) {
+        (from, to) if from == to => true,
+        (Type::Array(_), _) | (_, Type::Array(_)) => false,
+        (_, Type::String) | (_, Type::Bool) => true,
+        (Type::Int, Type::Float | Type::Char) => true,
+        (Type::Float, Type::Int) => true,
+        (Type::String, _) => true,
+        (Type::Char | Type::Bool, Type::Int) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::utils::{input_error, sorted_subfolders, subfolder_count};
+
+/// Directory listings shared by the commands and expressions of a run.
+///
+/// The interpreter reads the same folders over and over, in loops especially.
+/// Programs modifying their own folders while running have to disable it.
+pub struct FolderCache {
+    enabled: bool,
+
+    /// sorted subfolders indexed by folder
+    listings: RefCell<HashMap<String, Vec<String>>>,
+
+    /// subfolder count indexed by folder
+    counts: RefCell<HashMap<String, usize>>,
+
+    /// number of directories read and time spent reading them, for profiles
+    reads: Cell<(usize, Duration)>,
+}
+
+impl Default for FolderCache {
+    fn default() -> Self {
+        FolderCache::new(true)
+    }
+}
+
+impl FolderCache {
+    pub fn new(enabled: bool) -> Self {
+        FolderCache {
+            enabled,
+            listings: RefCell::new(HashMap::new()),
+            counts: RefCell::new(HashMap::new()),
+            reads: Cell::new((0, Duration::ZERO)),
+        }
+    }
+
+    /// Forget every listing, after the program changed its own folders
+    pub fn clear(&self) {
+        self.listings.borrow_mut().clear();
+        self.counts.borrow_mut().clear();
+    }
+
+    /// Number of directories read and time spent reading them
+    pub fn reads(&self) -> (usize, Duration) {
+        self.reads.get()
+    }
+
+    fn read<T>(&self, read: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = read();
+
+        let (count, time) = self.reads.get();
+        self.reads.set((count + 1, time + start.elapsed()));
+
+        result
+    }
+
+    pub fn sorted_subfolders(&self, folder: &str) -> std::io::Result<Vec<String>> {
+        if !self.enabled {
+            return self.read(|| sorted_subfolders(folder));
+        }
+
+        if let Some(subfolders) = self.listings.borrow().get(folder) {
+            return Ok(subfolders.clone());
+        }
+
+        let subfolders = self.read(|| sorted_subfolders(folder))?;
+        self.listings
+            .borrow_mut()
+            .insert(folder.to_owned(), subfolders.clone());
+
+        Ok(subfolders)
+    }
+
+    pub fn subfolder_count(&self, folder: &str) -> std::io::Result<usize> {
+        if !self.enabled {
+            return self.read(|| subfolder_count(folder));
+        }
+
+        if let Some(count) = self.counts.borrow().get(folder) {
+            return Ok(*count);
+        }
+
+        let count = self.read(|| subfolder_count(folder))?;
+        self.counts.borrow_mut().insert(folder.to_owned(), count);
+
+        Ok(count)
+    }
+
+    /// used in context of literal values
+    pub fn is_bit_set(&self, folder: &str) -> bool {
+        self.subfolder_count(folder).unwrap_or(0) != 0
+    }
+
+    /// read byte from folder
+    pub fn get_byte(&self, folder: &str) -> std::io::Result<u8> {
+        let hex_folders = self.sorted_subfolders(folder)?;
+        if hex_folders.len() != 2 {
+            return Err(input_error(format!(
+                "{} : invalid byte, found {} subfolders",
+                folder,
+                hex_folders.len()
+            )));
+        }
+
+        let left = self.sorted_subfolders(&hex_folders[0])?;
+        let right = self.sorted_subfolders(&hex_folders[1])?;
+
+        if left.len() != 4 {
+            return Err(input_error(format!(
+                "{} : invalid hex digit, found {} subfolders",
+                hex_folders[0],
+                left.len()
+            )));
+        }
+
+        if right.len() != 4 {
+            return Err(input_error(format!(
+                "{} : invalid hex digit, found {} subfolders",
+                hex_folders[1],
+                right.len()
+            )));
+        }
+
+        let mut i = 7;
+        let mut byte: u8 = 0;
+
+        for bit_folder in left {
+            byte |= ((self.is_bit_set(&bit_folder) as i32) << i) as u8;
+            i -= 1;
+        }
+
+        for bit_folder in right {
+            byte |= ((self.is_bit_set(&bit_folder) as i32) << i) as u8;
+            i -= 1;
+        }
+
+        Ok(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir::TempDir;
+
+    #[test]
+    fn listings_are_read_once_unless_disabled() {
+        let dir = TempDir::new();
+        let root = dir.path();
+        std::fs::create_dir(root.join("a")).unwrap();
+        let folder = root.to_str().unwrap();
+
+        let cached = FolderCache::new(true);
+        let uncached = FolderCache::new(false);
+        assert_eq!(cached.sorted_subfolders(folder).unwrap().len(), 1);
+        assert_eq!(cached.subfolder_count(folder).unwrap(), 1);
+
+        std::fs::create_dir(root.join("b")).unwrap();
+        assert_eq!(cached.sorted_subfolders(folder).unwrap().len(), 1);
+        assert_eq!(cached.subfolder_count(folder).unwrap(), 1);
+        assert_eq!(uncached.sorted_subfolders(folder).unwrap().len(), 2);
+        assert_eq!(uncached.subfolder_count(folder).unwrap(), 2);
+    }
+}
@@ -0,0 +1,560 @@
+//! Structural differences between two programs.
+//!
+//! Both programs are decoded into trees of commands and expressions. Lists of
+//! commands are aligned on their longest common subsequence, commands of the
+//! same kind left unmatched at the same place are compared field by field.
+
+use std::{
+    cell::RefCell,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    context::Context,
+    diff::{diff, Change},
+    disasm::{command_text, expression_text},
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+    },
+    scope::Scope,
+    utils::{input_error, sorted_subfolders},
+};
+
+/// Program of a git revision given as `<revision>:<folder>`, the folder being
+/// relative to the current one, rebuilt in a temporary folder.
+///
+/// Git only keeps folders holding files, the ones of the revision are created.
+pub struct Checkout {
+    /// what the program was given as
+    spec: String,
+
+    /// temporary folder, removed when dropped
+    root: PathBuf,
+
+    /// folder of the program in `root`
+    pub folder: String,
+}
+
+impl Checkout {
+    /// None when `spec` is a folder on disk or names no revision
+    pub fn new(spec: &str) -> std::io::Result<Option<Self>> {
+        // local folders come first, their names may hold ':'
+        if Path::new(spec).exists() {
+            return Ok(None);
+        }
+        let Some((revision, path)) = spec.split_once(':') else {
+            return Ok(None);
+        };
+
+        let checkout = Checkout::revision(revision, path)?;
+        if !Path::new(&checkout.folder).is_dir() {
+            return Err(input_error(format!(
+                "{spec} : no such folder in {revision}"
+            )));
+        }
+
+        Ok(Some(checkout))
+    }
+
+    /// Program in `path` at `revision`, its folder is missing when the revision
+    /// has no such folder
+    pub fn revision(revision: &str, path: &str) -> std::io::Result<Self> {
+        let spec = format!("{revision}:{path}");
+        let path = if path.is_empty() { "." } else { path };
+
+        let files = git(&[
+            "ls-tree",
+            "-r",
+            "--full-name",
+            "--name-only",
+            revision,
+            "--",
+            path,
+        ])
+        .map_err(|err| input_error(format!("{spec} : {err}")))?;
+        let prefix = git(&["rev-parse", "--show-prefix"])?;
+
+        let mut checkout = Checkout::temporary(&spec);
+        checkout.folder = checkout
+            .root
+            .join(prefix.trim())
+            .join(path)
+            .to_string_lossy()
+            .into_owned();
+
+        std::fs::create_dir_all(checkout.root.join(prefix.trim()))?;
+        for file in files.lines() {
+            if let Some(parent) = Path::new(file).parent() {
+                std::fs::create_dir_all(checkout.root.join(parent))?;
+            }
+        }
+
+        Ok(checkout)
+    }
+
+    /// Program without any command, shown as `spec`
+    pub fn empty(spec: &str) -> std::io::Result<Self> {
+        let checkout = Checkout::temporary(spec);
+        std::fs::create_dir_all(&checkout.folder)?;
+
+        Ok(checkout)
+    }
+
+    fn temporary(spec: &str) -> Self {
+        // distinct folders for the checkouts of a same process
+        static CHECKOUTS: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "folders-diff-{}-{}",
+            std::process::id(),
+            CHECKOUTS.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Checkout {
+            spec: spec.to_owned(),
+            folder: root.to_string_lossy().into_owned(),
+            root,
+        }
+    }
+
+    /// `text` with the temporary folder replaced by the revision
+    pub fn show(&self, text: &str) -> String {
+        text.replacen(&self.folder, &self.spec, 1)
+    }
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Folder made of the first `depth` folders of `path`, None for files above it
+pub fn program_root(path: &str, depth: usize) -> Option<String> {
+    let folders: Vec<&str> = path.split('/').collect();
+    if depth == 0 || folders.len() <= depth {
+        return None;
+    }
+
+    Some(folders[..depth].join("/"))
+}
+
+/// Top folder of the working tree, git gives diff drivers paths relative to it
+pub fn top_level() -> std::io::Result<String> {
+    Ok(git(&["rev-parse", "--show-toplevel"])?.trim().to_owned())
+}
+
+/// Programs of `roots` that were not compared yet by the running git command.
+///
+/// Git runs its diff driver once per changed file and numbers the runs, the
+/// programs already compared are listed in a file until the last run.
+pub fn first_comparisons(roots: Vec<String>) -> std::io::Result<Vec<String>> {
+    let run = |name| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+    };
+    let (Some(counter), Some(total)) = (run("GIT_DIFF_PATH_COUNTER"), run("GIT_DIFF_PATH_TOTAL"))
+    else {
+        return Ok(roots);
+    };
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::env::current_dir()?.hash(&mut hasher);
+    let list = std::env::temp_dir().join(format!("folders-diff-driver-{:x}", hasher.finish()));
+
+    // a list left by an interrupted command is not read by the first run
+    let mut compared = match counter {
+        1 => String::new(),
+        _ => std::fs::read_to_string(&list).unwrap_or_default(),
+    };
+    let roots: Vec<String> = roots
+        .into_iter()
+        .filter(|root| !compared.lines().any(|line| line == root))
+        .collect();
+    for root in &roots {
+        compared += root;
+        compared.push('\n');
+    }
+
+    if counter >= total {
+        let _ = std::fs::remove_file(&list);
+    } else {
+        std::fs::write(&list, compared)?;
+    }
+
+    Ok(roots)
+}
+
+/// Output of a git command
+fn git(args: &[&str]) -> std::io::Result<String> {
+    let output = std::process::Command::new("git").args(args).output()?;
+
+    if !output.status.success() {
+        return Err(input_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Command or expression decoded from a folder
+struct Node {
+    folder: String,
+
+    /// command name or expression type
+    kind: String,
+
+    /// the node as pseudo-code
+    text: String,
+
+    /// the node is a binary operation
+    operator: bool,
+
+    /// variables, literals, types... compared between nodes of the same kind
+    fields: Vec<(&'static str, String)>,
+
+    /// expressions the node is made of, compared one by one
+    operands: Vec<Node>,
+
+    /// named lists of commands or arguments, aligned before being compared
+    lists: Vec<List>,
+}
+
+#[derive(PartialEq)]
+struct List {
+    name: &'static str,
+
+    /// what the list holds, 'command' or 'argument'
+    item: &'static str,
+
+    nodes: Vec<Node>,
+}
+
+/// Nodes are equal when they mean the same, whatever their folders
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.fields == other.fields
+            && self.operands == other.operands
+            && self.lists == other.lists
+    }
+}
+
+/// Differences between the programs in `old` and `new`, one per line, each
+/// starting with the folder it was found in
+pub fn compare(old: &str, new: &str, context: &Rc<Context>) -> std::io::Result<Vec<String>> {
+    let decoder = Decoder {
+        scope: Rc::new(RefCell::new(Scope::new(None))),
+        context: context.clone(),
+    };
+
+    let mut differences = vec![];
+    compare_lists(
+        &decoder.block(old)?,
+        &decoder.block(new)?,
+        "command",
+        &mut differences,
+    );
+
+    Ok(differences)
+}
+
+/// Report the nodes inserted into and removed from a list, and the changes of
+/// the nodes found at the same place in both
+fn compare_lists(old: &[Node], new: &[Node], item: &str, differences: &mut Vec<String>) {
+    let changes = diff(old, new);
+
+    let (mut old_index, mut new_index) = (0, 0);
+    let mut position = 0;
+    while position < changes.len() {
+        // consecutive removals and insertions, which may be changes of the same node
+        let mut removed = vec![];
+        let mut added = vec![];
+        while let Some(change) = changes.get(position) {
+            match change {
+                Change::Kept(_) => break,
+                Change::Removed(node) => {
+                    removed.push((old_index, *node));
+                    old_index += 1;
+                }
+                Change::Added(node) => {
+                    added.push((new_index, *node));
+                    new_index += 1;
+                }
+            }
+            position += 1;
+        }
+
+        // removed nodes are matched in order with added nodes of the same kind
+        let mut next = 0;
+        for (index, old_node) in removed {
+            match added[next..]
+                .iter()
+                .position(|(_, node)| node.kind == old_node.kind)
+            {
+                Some(offset) => {
+                    report_inserted(&added[next..next + offset], item, differences);
+                    compare_nodes(old_node, added[next + offset].1, differences);
+                    next += offset + 1;
+                }
+                None => differences.push(format!(
+                    "{} : {item} removed from index {index} : {}",
+                    old_node.folder, old_node.text
+                )),
+            }
+        }
+        report_inserted(&added[next..], item, differences);
+
+        if position < changes.len() {
+            old_index += 1;
+            new_index += 1;
+            position += 1;
+        }
+    }
+}
+
+fn report_inserted(nodes: &[(usize, &Node)], item: &str, differences: &mut Vec<String>) {
+    for (index, node) in nodes {
+        differences.push(format!(
+            "{} : {item} inserted at index {index} : {}",
+            node.folder, node.text
+        ));
+    }
+}
+
+/// Changes between two nodes of the same kind
+fn compare_nodes(old: &Node, new: &Node, differences: &mut Vec<String>) {
+    for ((name, old_value), (_, new_value)) in old.fields.iter().zip(&new.fields) {
+        if old_value != new_value {
+            differences.push(format!(
+                "{} : {name} changed from {old_value} to {new_value}",
+                new.folder
+            ));
+        }
+    }
+
+    for (old_operand, new_operand) in old.operands.iter().zip(&new.operands) {
+        compare_expressions(old_operand, new_operand, differences);
+    }
+
+    let empty = vec![];
+    for list in &new.lists {
+        let old_nodes = old
+            .lists
+            .iter()
+            .find(|old_list| old_list.name == list.name)
+            .map_or(&empty, |old_list| &old_list.nodes);
+        compare_lists(old_nodes, &list.nodes, list.item, differences);
+    }
+
+    // lists only the old node has, an 'else' block removed
+    for list in &old.lists {
+        if !new.lists.iter().any(|new_list| new_list.name == list.name) {
+            compare_lists(&list.nodes, &[], list.item, differences);
+        }
+    }
+}
+
+fn compare_expressions(old: &Node, new: &Node, differences: &mut Vec<String>) {
+    if old.kind == new.kind {
+        compare_nodes(old, new, differences);
+    } else if old.operator && new.operator {
+        differences.push(format!(
+            "{} : operator changed from {} to {}",
+            new.folder, old.kind, new.kind
+        ));
+
+        for (old_operand, new_operand) in old.operands.iter().zip(&new.operands) {
+            compare_expressions(old_operand, new_operand, differences);
+        }
+    } else if old != new {
+        differences.push(format!(
+            "{} : expression changed from {} to {}",
+            new.folder, old.text, new.text
+        ));
+    }
+}
+
+struct Decoder {
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl Decoder {
+    fn block(&self, folder: &str) -> std::io::Result<Vec<Node>> {
+        sorted_subfolders(folder)?
+            .iter()
+            .map(|subfolder| self.command(&Command::new(subfolder, &self.scope, &self.context)?))
+            .collect()
+    }
+
+    fn arguments(&self, folder: &str) -> std::io::Result<List> {
+        let nodes = sorted_subfolders(folder)?
+            .iter()
+            .map(|argument| {
+                self.expression(&Expression::new(argument, &self.scope, &self.context)?)
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(List {
+            name: "arguments",
+            item: "argument",
+            nodes,
+        })
+    }
+
+    fn commands(&self, name: &'static str, folder: &str) -> std::io::Result<List> {
+        Ok(List {
+            name,
+            item: "command",
+            nodes: self.block(folder)?,
+        })
+    }
+
+    fn command(&self, cmd: &Command) -> std::io::Result<Node> {
+        let mut node = Node {
+            folder: cmd.folder().to_owned(),
+            kind: cmd.command_type.as_str().to_owned(),
+            text: command_text(cmd, &self.context)?,
+            operator: false,
+            fields: vec![],
+            operands: vec![],
+            lists: vec![],
+        };
+
+        match cmd.command_type {
+            CommandType::Declare => {
+                node.fields = vec![
+                    ("variable", format!("var_{}", cmd.variable_index()?)),
+                    ("type", cmd.declared_type()?.as_str().to_owned()),
+                ];
+            }
+
+            CommandType::Let => {
+                node.fields = vec![("variable", format!("var_{}", cmd.variable_index()?))];
+                node.operands = vec![self.expression(&cmd.expression()?)?];
+            }
+
+            CommandType::Input => {
+                node.fields = vec![("variable", format!("var_{}", cmd.variable_index()?))];
+            }
+
+            CommandType::Print
+            | CommandType::Return
+            | CommandType::CreateFolder
+            | CommandType::RemoveFolder => {
+                node.operands = vec![self.expression(&cmd.expression()?)?];
+            }
+
+            CommandType::Write => {
+                node.operands = vec![
+                    self.expression(&cmd.expression()?)?,
+                    self.expression(&cmd.value_expression()?)?,
+                ];
+            }
+
+            CommandType::Define => {
+                node.fields = vec![
+                    ("procedure", format!("proc_{}", cmd.procedure_index()?)),
+                    ("parameter count", cmd.parameter_count()?.to_string()),
+                ];
+                node.lists = vec![self.commands("body", cmd.body_folder()?)?];
+            }
+
+            CommandType::Call => {
+                node.fields = vec![("procedure", format!("proc_{}", cmd.procedure_index()?))];
+                node.lists = vec![self.arguments(cmd.arguments_folder()?)?];
+            }
+
+            CommandType::If | CommandType::While => {
+                node.operands = vec![self.expression(&cmd.expression()?)?];
+                node.lists = vec![self.commands("body", cmd.body_folder()?)?];
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    node.lists.push(self.commands("else", else_folder)?);
+                }
+            }
+        }
+
+        Ok(node)
+    }
+
+    fn expression(&self, exp: &Expression) -> std::io::Result<Node> {
+        let mut node = Node {
+            folder: exp.folder().to_owned(),
+            kind: format!("{:?}", exp.expression_type),
+            text: expression_text(exp, &self.context)?,
+            operator: false,
+            fields: vec![],
+            operands: vec![],
+            lists: vec![],
+        };
+
+        match exp.expression_type {
+            ExpressionType::Variable => {
+                node.fields = vec![("variable", format!("var_{}", exp.variable_index()?))];
+            }
+
+            ExpressionType::LiteralValue => {
+                node.fields = vec![("literal", node.text.clone())];
+            }
+
+            ExpressionType::Call => {
+                node.fields = vec![("procedure", format!("proc_{}", exp.procedure_index()?))];
+                node.lists = vec![self.arguments(exp.arguments_folder())?];
+            }
+
+            ExpressionType::Cast => {
+                node.fields = vec![("type", exp.target_type()?.as_str().to_owned())];
+                node.operands = vec![self.expression(&exp.cast_operand()?)?];
+            }
+
+            ExpressionType::Not => {
+                node.operands = vec![self.expression(&exp.operand()?)?];
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                node.operands = exp
+                    .sequence_operands()?
+                    .iter()
+                    .map(|operand| self.expression(operand))
+                    .collect::<std::io::Result<_>>()?;
+            }
+
+            _ => {
+                let (first, second) = exp.operands()?;
+                node.operator = true;
+                node.operands = vec![self.expression(&first)?, self.expression(&second)?];
+            }
+        }
+
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn program_roots_are_leading_folders() {
+        assert_eq!(
+            program_root("samples/HelloWorld/New folder/.keep", 2).as_deref(),
+            Some("samples/HelloWorld")
+        );
+        assert_eq!(
+            program_root("program/000/.keep", 1).as_deref(),
+            Some("program")
+        );
+        assert_eq!(program_root("samples/README.md", 2), None);
+        assert_eq!(program_root("README.md", 1), None);
+        assert_eq!(program_root("program/.keep", 0), None);
+    }
+}
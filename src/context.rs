@@ -0,0 +1,99 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    cache::FolderCache,
+    coverage::Coverage,
+    input::InputQueue,
+    items::{
+        procedure::Procedure,
+        variable::{Type, Variable},
+    },
+    numeric::NumericMode,
+    optimizer::Optimizations,
+    profile::Profiler,
+};
+
+/// default limit of nested procedure calls
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// Settings and state shared by every command and expression of a program
+pub struct Context {
+    /// results of the optimization pass, empty when optimizations are disabled
+    pub optimizations: Optimizations,
+
+    pub cache: FolderCache,
+
+    /// commands and expressions beyond the base language are allowed
+    pub extensions: bool,
+
+    /// comparisons and logical operations give Bool values instead of 1 or 0
+    pub booleans: bool,
+
+    /// folder of the program, commands modifying folders are confined to it
+    pub root: String,
+
+    /// procedures registered by 'define' commands, indexed by name
+    pub procedures: RefCell<HashMap<usize, Procedure>>,
+
+    /// return value of every running procedure call, innermost call last
+    pub calls: RefCell<Vec<Option<Variable>>>,
+
+    /// maximum number of nested procedure calls
+    pub max_call_depth: usize,
+
+    /// lines read by 'input' commands
+    pub input: InputQueue,
+
+    /// overflow and division by zero handling
+    pub numeric: NumericMode,
+
+    /// hit counts and timings of the folders run
+    pub profiler: Profiler,
+
+    /// commands run and 'if' bodies entered
+    pub coverage: Coverage,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            optimizations: Optimizations::default(),
+            cache: FolderCache::default(),
+            extensions: false,
+            booleans: false,
+            root: String::new(),
+            procedures: RefCell::new(HashMap::new()),
+            calls: RefCell::new(vec![]),
+            max_call_depth: MAX_CALL_DEPTH,
+            input: InputQueue::default(),
+            numeric: NumericMode::default(),
+            profiler: Profiler::default(),
+            coverage: Coverage::default(),
+        }
+    }
+}
+
+impl Context {
+    /// A 'return' command ran in the innermost procedure call, remaining commands are skipped
+    pub fn is_returning(&self) -> bool {
+        matches!(self.calls.borrow().last(), Some(Some(_)))
+    }
+
+    /// Type of the results of comparisons and logical operations
+    pub fn truth_type(&self) -> Type {
+        if self.booleans {
+            Type::Bool
+        } else {
+            Type::Int
+        }
+    }
+
+    /// Result of a comparison or a logical operation
+    pub fn truth(&self, value: bool) -> Variable {
+        if self.booleans {
+            Variable::Bool(Some(value))
+        } else {
+            Variable::Int(Some(value as i32))
+        }
+    }
+}
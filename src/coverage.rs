@@ -0,0 +1,204 @@
+//! Command folders run and 'if' bodies entered or skipped during a run.
+//!
+//! Reports are laid over the disassembled program (see `disasm`): line numbers
+//! are the ones of its listing.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::disasm::Line;
+
+/// Format of coverage reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CoverageFormat {
+    /// LCOV tracefile, read by genhtml and most CI services
+    #[default]
+    Lcov,
+
+    /// disassembled program annotated with hit counts
+    Html,
+}
+
+/// Coverage of a run, does nothing unless enabled
+#[derive(Default)]
+pub struct Coverage {
+    enabled: bool,
+
+    /// number of times each command folder ran
+    hits: RefCell<HashMap<String, usize>>,
+
+    /// number of times the body of each 'if' command was entered and skipped
+    branches: RefCell<HashMap<String, (usize, usize)>>,
+}
+
+impl Coverage {
+    pub fn new(enabled: bool) -> Self {
+        Coverage {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// The command in `folder` runs
+    pub fn hit(&self, folder: &str) {
+        if self.enabled {
+            *self.hits.borrow_mut().entry(folder.to_owned()).or_default() += 1;
+        }
+    }
+
+    /// The body of the 'if' command in `folder` was entered, or skipped
+    pub fn branch(&self, folder: &str, entered: bool) {
+        if self.enabled {
+            let mut branches = self.branches.borrow_mut();
+            let (taken, skipped) = branches.entry(folder.to_owned()).or_default();
+
+            if entered {
+                *taken += 1;
+            } else {
+                *skipped += 1;
+            }
+        }
+    }
+
+    pub fn report(&self, format: CoverageFormat, root: &str, listing: &[Line]) -> String {
+        match format {
+            CoverageFormat::Lcov => self.lcov(root, listing),
+            CoverageFormat::Html => self.html(root, listing),
+        }
+    }
+
+    fn hits(&self, folder: &str) -> usize {
+        self.hits.borrow().get(folder).copied().unwrap_or(0)
+    }
+
+    /// Times the body of the 'if' command in `folder` was entered and skipped
+    fn branches(&self, folder: &str) -> (usize, usize) {
+        self.branches
+            .borrow()
+            .get(folder)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Numbers of runs and of branches of the 'if' commands, with how many were taken
+    fn summary(&self, listing: &[Line]) -> [usize; 4] {
+        let mut summary = [0; 4];
+
+        for line in listing {
+            let Some(folder) = &line.folder else {
+                continue;
+            };
+
+            summary[0] += 1;
+            summary[1] += (self.hits(folder) > 0) as usize;
+
+            if line.branching {
+                let (entered, skipped) = self.branches(folder);
+                summary[2] += 2;
+                summary[3] += (entered > 0) as usize + (skipped > 0) as usize;
+            }
+        }
+
+        summary
+    }
+
+    fn lcov(&self, root: &str, listing: &[Line]) -> String {
+        let mut report = format!("TN:\nSF:{root}\n");
+
+        for (number, line) in listing.iter().enumerate() {
+            let Some(folder) = &line.folder else {
+                continue;
+            };
+
+            let number = number + 1;
+            let hits = self.hits(folder);
+            report += &format!("DA:{number},{hits}\n");
+
+            // branches of commands that never ran are marked with '-'
+            if line.branching {
+                let (entered, skipped) = match self.branches(folder) {
+                    _ if hits == 0 => ("-".to_owned(), "-".to_owned()),
+                    (entered, skipped) => (entered.to_string(), skipped.to_string()),
+                };
+                report += &format!("BRDA:{number},0,0,{entered}\nBRDA:{number},0,1,{skipped}\n");
+            }
+        }
+
+        let [lines, lines_hit, branch_count, branches_hit] = self.summary(listing);
+        report += &format!(
+            "BRF:{branch_count}\nBRH:{branches_hit}\nLF:{lines}\nLH:{lines_hit}\nend_of_record\n"
+        );
+
+        report
+    }
+
+    fn html(&self, root: &str, listing: &[Line]) -> String {
+        let [lines, lines_hit, branch_count, branches_hit] = self.summary(listing);
+        let root = escape(root);
+
+        let mut report = format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Coverage of {root}</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; font-family: monospace; }}
+td {{ padding: 0 1em; white-space: pre; }}
+td.count {{ text-align: right; color: #666; }}
+tr.hit {{ background: #dfd; }}
+tr.missed {{ background: #fdd; }}
+tr.partial {{ background: #ffd; }}
+</style>
+</head>
+<body>
+<h1>Coverage of {root}</h1>
+<p>{lines_hit} of {lines} commands run, {branches_hit} of {branch_count} 'if' branches taken</p>
+<table>
+<tr><th>line</th><th>hits</th><th>'if' body</th><th>command</th></tr>
+"
+        );
+
+        for (number, line) in listing.iter().enumerate() {
+            let text = escape(&line.to_string());
+
+            let Some(folder) = &line.folder else {
+                report += &format!(
+                    "<tr><td class=\"count\">{}</td><td></td><td></td><td>{text}</td></tr>\n",
+                    number + 1
+                );
+                continue;
+            };
+
+            let hits = self.hits(folder);
+            let (entered, skipped) = self.branches(folder);
+            let class = if hits == 0 {
+                "missed"
+            } else if line.branching && (entered == 0 || skipped == 0) {
+                "partial"
+            } else {
+                "hit"
+            };
+            let branch = if line.branching {
+                format!("entered {entered}, skipped {skipped}")
+            } else {
+                String::new()
+            };
+
+            report += &format!(
+                "<tr class=\"{class}\" title=\"{}\"><td class=\"count\">{}</td><td class=\"count\">{hits}</td><td>{branch}</td><td>{text}</td></tr>\n",
+                escape(folder),
+                number + 1,
+            );
+        }
+
+        report + "</table>\n</body>\n</html>\n"
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,107 @@
+//! Line diffs, shown as unified diffs.
+
+/// Change of one item, a line usually, between two lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<T> {
+    Kept(T),
+    Removed(T),
+    Added(T),
+}
+
+/// Shortest list of changes turning `old` into `new`, from their longest common subsequence.
+/// Kept items are the ones of `old`
+pub fn diff<'a, T: PartialEq>(old: &'a [T], new: &'a [T]) -> Vec<Change<&'a T>> {
+    // common[i][j] : length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Kept(&old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(&old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(&new[j]));
+            j += 1;
+        }
+    }
+
+    changes
+}
+
+/// Hunks of a unified diff from `old` to `new`, with `context` unchanged lines
+/// around changes. Empty when both are the same
+pub fn unified(old: &[String], new: &[String], context: usize) -> Vec<String> {
+    let changes = diff(old, new);
+
+    // line numbers in old and new before each change
+    let mut positions = Vec::with_capacity(changes.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for change in &changes {
+        positions.push((old_line, new_line));
+        match change {
+            Change::Kept(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Change::Removed(_) => old_line += 1,
+            Change::Added(_) => new_line += 1,
+        }
+    }
+
+    let mut lines = vec![];
+    let mut start = 0;
+    while let Some(first) = changes[start..]
+        .iter()
+        .position(|change| !matches!(change, Change::Kept(_)))
+    {
+        // a hunk goes on while changes are less than two contexts apart
+        let first = start + first;
+        let mut last = first;
+        while let Some(next) = changes[last + 1..]
+            .iter()
+            .position(|change| !matches!(change, Change::Kept(_)))
+            .filter(|gap| *gap <= 2 * context)
+        {
+            last += next + 1;
+        }
+
+        let begin = first.saturating_sub(context).max(start);
+        let end = (last + context + 1).min(changes.len());
+        let hunk = &changes[begin..end];
+        let (old_start, new_start) = positions[begin];
+
+        // empty ranges start at the line before them
+        let range = |start: usize, shown: fn(&Change<&String>) -> bool| {
+            let count = hunk.iter().filter(|change| shown(change)).count();
+            format!("{},{count}", if count == 0 { start } else { start + 1 })
+        };
+        lines.push(format!(
+            "@@ -{} +{} @@",
+            range(old_start, |change| !matches!(change, Change::Added(_))),
+            range(new_start, |change| !matches!(change, Change::Removed(_))),
+        ));
+        lines.extend(hunk.iter().map(|change| match change {
+            Change::Kept(line) => format!(" {line}"),
+            Change::Removed(line) => format!("-{line}"),
+            Change::Added(line) => format!("+{line}"),
+        }));
+
+        start = end;
+    }
+
+    lines
+}
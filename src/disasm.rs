@@ -0,0 +1,278 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+        variable::Variable,
+    },
+    scope::Scope,
+    utils::sorted_subfolders,
+};
+
+/// One line of a disassembled program
+pub struct Line {
+    /// folder of the command shown on this line, None for closing braces and 'else'
+    pub folder: Option<String>,
+
+    /// nesting level of the line
+    pub depth: usize,
+
+    /// the line is an 'if' command, whose body can be entered or skipped
+    pub branching: bool,
+
+    pub text: String,
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", "    ".repeat(self.depth), self.text)
+    }
+}
+
+/// Listing of the program in `folder` as pseudo-code, one command per line
+pub fn disassemble(folder: &str, context: &Rc<Context>) -> std::io::Result<Vec<Line>> {
+    let mut disassembler = Disassembler::new(context);
+    disassembler.block(folder, 0)?;
+
+    Ok(disassembler.lines)
+}
+
+/// Text of the line showing `cmd` in a listing, without the block it opens
+pub fn command_text(cmd: &Command, context: &Rc<Context>) -> std::io::Result<String> {
+    Disassembler::new(context).header(cmd)
+}
+
+/// `exp` as pseudo-code
+pub fn expression_text(exp: &Expression, context: &Rc<Context>) -> std::io::Result<String> {
+    Disassembler::new(context).expression(exp, false)
+}
+
+struct Disassembler {
+    lines: Vec<Line>,
+
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl Disassembler {
+    fn new(context: &Rc<Context>) -> Self {
+        Disassembler {
+            lines: vec![],
+            scope: Rc::new(RefCell::new(Scope::new(None))),
+            context: context.clone(),
+        }
+    }
+
+    fn line(&mut self, folder: Option<&str>, depth: usize, text: String) {
+        self.lines.push(Line {
+            folder: folder.map(|folder| folder.to_owned()),
+            depth,
+            branching: false,
+            text,
+        });
+    }
+
+    fn block(&mut self, folder: &str, depth: usize) -> std::io::Result<()> {
+        for subfolder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&subfolder, &self.scope, &self.context)?;
+            self.command(&cmd, depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn arguments(&self, folder: &str) -> std::io::Result<String> {
+        let mut arguments = vec![];
+        for argument in sorted_subfolders(folder)? {
+            let exp = Expression::new(&argument, &self.scope, &self.context)?;
+            arguments.push(self.expression(&exp, false)?);
+        }
+
+        Ok(arguments.join(", "))
+    }
+
+    fn command(&mut self, cmd: &Command, depth: usize) -> std::io::Result<()> {
+        let folder = Some(cmd.folder());
+        let text = self.header(cmd)?;
+
+        match cmd.command_type {
+            CommandType::Define => {
+                self.line(folder, depth, format!("{text} {{"));
+                self.block(cmd.body_folder()?, depth + 1)?;
+            }
+
+            CommandType::If | CommandType::While => {
+                self.line(folder, depth, format!("{text} {{"));
+                if let Some(line) = self.lines.last_mut() {
+                    line.branching = cmd.command_type == CommandType::If;
+                }
+                self.block(cmd.body_folder()?, depth + 1)?;
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    self.line(None, depth, "} else {".to_owned());
+                    self.block(else_folder, depth + 1)?;
+                }
+            }
+
+            _ => {
+                self.line(folder, depth, text);
+                return Ok(());
+            }
+        }
+
+        // closing braces belong to no command
+        self.line(None, depth, "}".to_owned());
+
+        Ok(())
+    }
+
+    /// Text of the line showing `cmd`, without the block it opens
+    fn header(&self, cmd: &Command) -> std::io::Result<String> {
+        Ok(match cmd.command_type {
+            CommandType::Declare => format!(
+                "declare var_{} : {}",
+                cmd.variable_index()?,
+                cmd.declared_type()?.as_str()
+            ),
+            CommandType::Let => format!(
+                "let var_{} = {}",
+                cmd.variable_index()?,
+                self.expression(&cmd.expression()?, false)?
+            ),
+            CommandType::Input => format!("input var_{}", cmd.variable_index()?),
+            CommandType::Print
+            | CommandType::CreateFolder
+            | CommandType::RemoveFolder
+            | CommandType::Return => format!(
+                "{} {}",
+                cmd.command_type.as_str(),
+                self.expression(&cmd.expression()?, false)?
+            ),
+            CommandType::Write => format!(
+                "write {}, {}",
+                self.expression(&cmd.expression()?, false)?,
+                self.expression(&cmd.value_expression()?, false)?
+            ),
+            CommandType::Call => format!(
+                "call proc_{}({})",
+                cmd.procedure_index()?,
+                self.arguments(cmd.arguments_folder()?)?
+            ),
+            CommandType::Define => {
+                let parameters: Vec<String> = (0..cmd.parameter_count()?)
+                    .map(|i| format!("var_{i}"))
+                    .collect();
+
+                format!(
+                    "define proc_{}({})",
+                    cmd.procedure_index()?,
+                    parameters.join(", ")
+                )
+            }
+            CommandType::If | CommandType::While => format!(
+                "{} {}",
+                cmd.command_type.as_str(),
+                self.expression(&cmd.expression()?, false)?
+            ),
+        })
+    }
+
+    /// Expressions nested in another one are parenthesized
+    fn expression(&self, exp: &Expression, nested: bool) -> std::io::Result<String> {
+        let text = match exp.expression_type {
+            ExpressionType::Variable => return Ok(format!("var_{}", exp.variable_index()?)),
+
+            ExpressionType::LiteralValue => {
+                let value = exp.get_literal_value()?;
+                return Ok(match &value {
+                    Variable::String(Some(value)) => format!("{value:?}"),
+                    Variable::Char(Some(value)) => format!("{value:?}"),
+                    Variable::Float(Some(value)) => format!("{value:?}"),
+                    _ => value.to_string(),
+                });
+            }
+
+            ExpressionType::Call => {
+                return Ok(format!(
+                    "proc_{}({})",
+                    exp.procedure_index()?,
+                    self.arguments(exp.arguments_folder())?
+                ))
+            }
+
+            ExpressionType::Not => format!("!{}", self.expression(&exp.operand()?, true)?),
+
+            ExpressionType::Cast => format!(
+                "{} as {}",
+                self.expression(&exp.cast_operand()?, true)?,
+                exp.target_type()?.as_str()
+            ),
+
+            ExpressionType::IndexGet => {
+                let operands = exp.sequence_operands()?;
+                return Ok(format!(
+                    "{}[{}]",
+                    self.expression(&operands[0], true)?,
+                    self.expression(&operands[1], false)?
+                ));
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                let name = match exp_type {
+                    ExpressionType::IndexSet => "set",
+                    ExpressionType::Length => "len",
+                    ExpressionType::Push => "push",
+                    ExpressionType::Substring => "substr",
+                    _ => unreachable!("{exp_type:?} is not a sequence operation"),
+                };
+
+                let operands = exp
+                    .sequence_operands()?
+                    .iter()
+                    .map(|operand| self.expression(operand, false))
+                    .collect::<std::io::Result<Vec<String>>>()?;
+                return Ok(format!("{name}({})", operands.join(", ")));
+            }
+
+            exp_type => {
+                let operator = match exp_type {
+                    ExpressionType::Add => "+",
+                    ExpressionType::Substract => "-",
+                    ExpressionType::Multiply => "*",
+                    ExpressionType::Divide => "/",
+                    ExpressionType::EqualTo => "==",
+                    ExpressionType::GreaterThan => ">",
+                    ExpressionType::LessThan => "<",
+                    ExpressionType::Modulo => "%",
+                    ExpressionType::NotEqualTo => "!=",
+                    ExpressionType::And => "&&",
+                    ExpressionType::Or => "||",
+                    ExpressionType::BitAnd => "&",
+                    ExpressionType::BitOr => "|",
+                    ExpressionType::BitXor => "^",
+                    ExpressionType::ShiftLeft => "<<",
+                    ExpressionType::ShiftRight => ">>",
+                    ExpressionType::IntDivide => "//",
+                    _ => unreachable!("{exp_type:?} is not a binary operation"),
+                };
+
+                let (first, second) = exp.operands()?;
+                format!(
+                    "{} {operator} {}",
+                    self.expression(&first, true)?,
+                    self.expression(&second, true)?
+                )
+            }
+        };
+
+        if nested {
+            Ok(format!("({text})"))
+        } else {
+            Ok(text)
+        }
+    }
+}
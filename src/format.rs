@@ -0,0 +1,363 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    context::Context,
+    items::command::{Command, CommandType},
+    scope::Scope,
+    utils::{input_error, sorted_subfolders},
+};
+
+/// prefix of the temporary names used while renaming a folder's children
+const TEMPORARY_PREFIX: &str = ".folders-fmt-";
+
+/// Canonical names for the children of one folder, in execution order
+pub struct FolderPlan {
+    pub folder: String,
+
+    /// current name and canonical name of each subfolder
+    pub names: Vec<(String, String)>,
+}
+
+impl FolderPlan {
+    pub fn is_formatted(&self) -> bool {
+        self.names.iter().all(|(name, canonical)| name == canonical)
+    }
+}
+
+/// Compute the canonical layout of the program in `folder`.
+///
+/// Every subfolder is named after its index, zero-padded to the width of the
+/// largest index of its siblings. Command folders also carry the command type
+/// as suffix (ex. "03 - while"). Plans are listed children first so that they
+/// can be applied one after the other.
+pub fn plan(folder: &str, context: &Rc<Context>) -> std::io::Result<Vec<FolderPlan>> {
+    let mut formatter = Formatter {
+        plans: vec![],
+        scope: Rc::new(RefCell::new(Scope::new(None))),
+        context: context.clone(),
+    };
+    formatter.block(folder)?;
+
+    Ok(formatter.plans)
+}
+
+/// Rename folders as planned, only touching the ones that are not formatted.
+/// On error, the folders already renamed get their names back
+pub fn apply(plans: &[FolderPlan]) -> std::io::Result<()> {
+    // the program folder is planned last
+    let Some(root) = plans.last() else {
+        return Ok(());
+    };
+    let planned: HashMap<&str, &FolderPlan> = plans
+        .iter()
+        .map(|plan| (plan.folder.as_str(), plan))
+        .collect();
+    let layout = Layout::read(&root.folder, &planned)?;
+
+    let mut renames = vec![];
+    let result = rename_all(plans, &mut renames).and_then(|()| {
+        if Layout::read(&root.folder, &HashMap::new())? != layout {
+            return Err(input_error(format!(
+                "{} : folders are not named and ordered as planned after formatting",
+                root.folder
+            )));
+        }
+
+        Ok(())
+    });
+
+    let Err(error) = result else {
+        return Ok(());
+    };
+
+    let mut message = error.to_string();
+    for (from, to) in renames.iter().rev() {
+        if let Err(undo_error) = std::fs::rename(to, from) {
+            message += &format!(
+                ", {} could not be renamed back to {} : {undo_error}",
+                to.display(),
+                from.display()
+            );
+        }
+    }
+
+    Err(std::io::Error::new(error.kind(), message))
+}
+
+/// Apply the plans, every rename done is pushed to `renames`
+fn rename_all(plans: &[FolderPlan], renames: &mut Vec<(PathBuf, PathBuf)>) -> std::io::Result<()> {
+    let mut rename = |from: PathBuf, to: PathBuf| -> std::io::Result<()> {
+        std::fs::rename(&from, &to)?;
+        renames.push((from, to));
+        Ok(())
+    };
+
+    for plan in plans.iter().filter(|plan| !plan.is_formatted()) {
+        let folder = Path::new(&plan.folder);
+
+        // two passes so that a canonical name never collides with a sibling
+        for (i, (name, _)) in plan.names.iter().enumerate() {
+            rename(
+                folder.join(name),
+                folder.join(format!("{TEMPORARY_PREFIX}{i}")),
+            )?;
+        }
+        for (i, (_, canonical)) in plan.names.iter().enumerate() {
+            rename(
+                folder.join(format!("{TEMPORARY_PREFIX}{i}")),
+                folder.join(canonical),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Names of a folder tree, subfolders in execution order
+#[derive(PartialEq)]
+struct Layout {
+    name: String,
+    subfolders: Vec<Layout>,
+}
+
+impl Layout {
+    /// Layout of `folder` once the subfolders of the `planned` folders have their canonical names
+    fn read(folder: &str, planned: &HashMap<&str, &FolderPlan>) -> std::io::Result<Self> {
+        let mut subfolders = vec![];
+        for subfolder in sorted_subfolders(folder)? {
+            let mut layout = Layout::read(&subfolder, planned)?;
+
+            let canonical = planned.get(folder).and_then(|plan| {
+                plan.names
+                    .iter()
+                    .find(|(name, _)| *name == layout.name)
+                    .map(|(_, canonical)| canonical.clone())
+            });
+            if let Some(canonical) = canonical {
+                layout.name = canonical;
+            }
+
+            subfolders.push(layout);
+        }
+
+        let name = Path::new(folder)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Layout { name, subfolders })
+    }
+}
+
+struct Formatter {
+    plans: Vec<FolderPlan>,
+
+    /// scope handed to commands, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl Formatter {
+    /// Folder holding a list of commands
+    fn block(&mut self, folder: &str) -> std::io::Result<()> {
+        let mut suffixes = vec![];
+
+        for subfolder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&subfolder, &self.scope, &self.context)?;
+            self.command(&cmd)?;
+
+            suffixes.push(Some(cmd.command_type.as_str().to_owned()));
+        }
+
+        self.push_plan(folder, &suffixes)
+    }
+
+    fn command(&mut self, cmd: &Command) -> std::io::Result<()> {
+        let has_body = matches!(
+            cmd.command_type,
+            CommandType::If | CommandType::While | CommandType::Define
+        );
+
+        for subfolder in cmd.subfolders() {
+            if has_body
+                && (subfolder == cmd.body_folder()?
+                    || Some(subfolder.as_str()) == cmd.else_folder())
+            {
+                self.block(subfolder)?;
+            } else {
+                self.folder(subfolder)?;
+            }
+        }
+
+        self.push_plan(cmd.folder(), &vec![None; cmd.subfolders().len()])
+    }
+
+    /// Any other folder, only the count or the order of its subfolders matters
+    fn folder(&mut self, folder: &str) -> std::io::Result<()> {
+        let subfolders = sorted_subfolders(folder)?;
+        for subfolder in &subfolders {
+            self.folder(subfolder)?;
+        }
+
+        self.push_plan(folder, &vec![None; subfolders.len()])
+    }
+
+    fn push_plan(&mut self, folder: &str, suffixes: &[Option<String>]) -> std::io::Result<()> {
+        let subfolders = sorted_subfolders(folder)?;
+        let width = subfolders.len().saturating_sub(1).to_string().len();
+
+        let names: Vec<(String, String)> = subfolders
+            .iter()
+            .zip(suffixes)
+            .enumerate()
+            .map(|(i, (subfolder, suffix))| {
+                let name = Path::new(subfolder)
+                    .file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_owned();
+
+                let canonical = match suffix {
+                    Some(suffix) => format!("{i:0width$} - {suffix}"),
+                    None => format!("{i:0width$}"),
+                };
+
+                (name, canonical)
+            })
+            .collect();
+
+        // canonical names have to be read in the same order as the current ones
+        let ordered = names.windows(2).all(|pair| {
+            human_sort::compare(&pair[0].1.to_lowercase(), &pair[1].1.to_lowercase())
+                == Ordering::Less
+        });
+        if !ordered {
+            return Err(input_error(format!(
+                "{folder} : canonical names would change the execution order"
+            )));
+        }
+
+        self.plans.push(FolderPlan {
+            folder: folder.to_owned(),
+            names,
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir::TempDir;
+
+    /// Create `folders` below `dir`
+    fn create(dir: &TempDir, folders: &[&str]) {
+        for folder in folders {
+            std::fs::create_dir_all(dir.path().join(folder)).unwrap();
+        }
+    }
+
+    /// Every folder below `dir`, sorted
+    fn folders(dir: &TempDir) -> Vec<String> {
+        let mut tree = vec![];
+        let mut pending = vec![dir.path().to_path_buf()];
+
+        while let Some(folder) = pending.pop() {
+            for entry in std::fs::read_dir(&folder).unwrap() {
+                let path = entry.unwrap().path();
+                tree.push(path.strip_prefix(dir.path()).unwrap().display().to_string());
+                pending.push(path);
+            }
+        }
+
+        tree.sort();
+        tree
+    }
+
+    fn plan(folder: String, names: &[(&str, &str)]) -> FolderPlan {
+        FolderPlan {
+            folder,
+            names: names
+                .iter()
+                .map(|(name, canonical)| (name.to_string(), canonical.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn failed_rename_is_rolled_back() {
+        let dir = TempDir::new();
+        create(&dir, &["root/New folder/a", "root/New folder (2)"]);
+        let tree = folders(&dir);
+
+        let plans = [
+            plan(dir.join("root/New folder"), &[("a", "0")]),
+            plan(
+                dir.join("root"),
+                &[
+                    ("New folder", "0"),
+                    ("New folder (2)", "1"),
+                    ("missing", "2"),
+                ],
+            ),
+        ];
+
+        assert!(apply(&plans).is_err());
+        assert_eq!(folders(&dir), tree);
+    }
+
+    #[test]
+    fn changed_order_is_rolled_back() {
+        let dir = TempDir::new();
+        create(&dir, &["root/1/a", "root/2"]);
+        let tree = folders(&dir);
+
+        // 'b' would now run after 'a', which used to be second
+        let plans = [plan(dir.join("root"), &[("1", "b"), ("2", "a")])];
+
+        let error = apply(&plans).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("not named and ordered as planned"));
+        assert_eq!(folders(&dir), tree);
+    }
+
+    #[test]
+    fn swapped_folders_of_a_same_shape_are_rolled_back() {
+        let dir = TempDir::new();
+        create(&dir, &["root/1/x", "root/2/y"]);
+        let tree = folders(&dir);
+
+        // both folders hold one subfolder, only their names tell them apart
+        let plans = [plan(dir.join("root"), &[("1", "b"), ("2", "a")])];
+
+        assert!(apply(&plans).is_err());
+        assert_eq!(folders(&dir), tree);
+    }
+
+    #[test]
+    fn planned_names_are_applied() {
+        let dir = TempDir::new();
+        create(&dir, &["root/New folder/a", "root/New folder (2)"]);
+
+        let plans = [
+            plan(dir.join("root/New folder"), &[("a", "0")]),
+            plan(
+                dir.join("root"),
+                &[("New folder", "0"), ("New folder (2)", "1")],
+            ),
+        ];
+
+        apply(&plans).unwrap();
+        assert_eq!(folders(&dir), ["root", "root/0", "root/0/0", "root/1"]);
+    }
+}
@@ -0,0 +1,283 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use crate::{
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+        variable::Variable,
+    },
+    scope::Scope,
+    utils::sorted_subfolders,
+};
+
+/// Output format of the program graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Graphviz DOT language
+    Dot,
+}
+
+/// Render the syntax tree of the program in `folder`.
+///
+/// With `show_folders`, every node is linked to the folder it is decoded from.
+pub fn graph(
+    folder: &str,
+    format: Format,
+    show_folders: bool,
+    context: &Rc<Context>,
+) -> std::io::Result<String> {
+    match format {
+        Format::Dot => {
+            let mut graph = DotGraph {
+                lines: vec![],
+                node_count: 0,
+                show_folders,
+                scope: Rc::new(RefCell::new(Scope::new(None))),
+                context: context.clone(),
+            };
+
+            let root = graph.node("program", "box", "bold", folder);
+            graph.block(folder, &root, None)?;
+
+            let mut dot = vec![
+                "digraph program {".to_owned(),
+                "\tordering=out;".to_owned(),
+                "\tnode [fontname=\"monospace\"];".to_owned(),
+            ];
+            dot.extend(graph.lines.into_iter().map(|line| format!("\t{line}")));
+            dot.push("}".to_owned());
+
+            Ok(dot.join("\n"))
+        }
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+struct DotGraph {
+    lines: Vec<String>,
+    node_count: usize,
+    show_folders: bool,
+
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl DotGraph {
+    /// Add a node decoded from `folder`, returns its identifier
+    fn node(&mut self, label: &str, shape: &str, style: &str, folder: &str) -> String {
+        let id = format!("n{}", self.node_count);
+        self.node_count += 1;
+
+        self.lines.push(format!(
+            "{id} [label=\"{}\", shape={shape}, style=\"{style}\"];",
+            escape(label)
+        ));
+
+        if self.show_folders {
+            let name = Path::new(folder)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| folder.to_owned());
+
+            self.lines.push(format!(
+                "{id}_folder [label=\"{}\", tooltip=\"{}\", shape=folder, color=gray, fontcolor=gray];",
+                escape(&name),
+                escape(folder)
+            ));
+            self.lines.push(format!(
+                "{id} -> {id}_folder [style=dashed, color=gray, arrowhead=none];"
+            ));
+        }
+
+        id
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        match label {
+            Some(label) => self
+                .lines
+                .push(format!("{from} -> {to} [label=\"{}\"];", escape(label))),
+            None => self.lines.push(format!("{from} -> {to};")),
+        }
+    }
+
+    fn block(&mut self, folder: &str, parent: &str, label: Option<&str>) -> std::io::Result<()> {
+        for subfolder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&subfolder, &self.scope, &self.context)?;
+            let id = self.command(&cmd)?;
+            self.edge(parent, &id, label);
+        }
+
+        Ok(())
+    }
+
+    fn arguments(&mut self, folder: &str, parent: &str) -> std::io::Result<()> {
+        for argument in sorted_subfolders(folder)? {
+            let exp = Expression::new(&argument, &self.scope, &self.context)?;
+            let id = self.expression(&exp)?;
+            self.edge(parent, &id, Some("argument"));
+        }
+
+        Ok(())
+    }
+
+    fn command(&mut self, cmd: &Command) -> std::io::Result<String> {
+        let label = match cmd.command_type {
+            CommandType::Declare => format!(
+                "declare var_{} : {}",
+                cmd.variable_index()?,
+                cmd.declared_type()?.as_str()
+            ),
+            CommandType::Let | CommandType::Input => format!(
+                "{} var_{}",
+                cmd.command_type.as_str(),
+                cmd.variable_index()?
+            ),
+            CommandType::Define => format!(
+                "define proc_{} : {} parameters",
+                cmd.procedure_index()?,
+                cmd.parameter_count()?
+            ),
+            CommandType::Call => format!("call proc_{}", cmd.procedure_index()?),
+            _ => cmd.command_type.as_str().to_owned(),
+        };
+        let id = self.node(&label, "box", "rounded", cmd.folder());
+
+        match cmd.command_type {
+            CommandType::Let | CommandType::Print => {
+                let exp = self.expression(&cmd.expression()?)?;
+                self.edge(&id, &exp, None);
+            }
+
+            CommandType::Return => {
+                let exp = self.expression(&cmd.expression()?)?;
+                self.edge(&id, &exp, None);
+            }
+
+            CommandType::Define => {
+                self.block(cmd.body_folder()?, &id, Some("body"))?;
+            }
+
+            CommandType::Call => {
+                self.arguments(cmd.arguments_folder()?, &id)?;
+            }
+
+            CommandType::CreateFolder | CommandType::RemoveFolder => {
+                let path = self.expression(&cmd.expression()?)?;
+                self.edge(&id, &path, Some("path"));
+            }
+
+            CommandType::Write => {
+                let path = self.expression(&cmd.expression()?)?;
+                self.edge(&id, &path, Some("path"));
+                let value = self.expression(&cmd.value_expression()?)?;
+                self.edge(&id, &value, Some("value"));
+            }
+
+            CommandType::If | CommandType::While => {
+                let exp = self.expression(&cmd.expression()?)?;
+                self.edge(&id, &exp, Some("condition"));
+                self.block(cmd.body_folder()?, &id, Some("body"))?;
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    self.block(else_folder, &id, Some("else"))?;
+                }
+            }
+
+            CommandType::Declare | CommandType::Input => {}
+        }
+
+        Ok(id)
+    }
+
+    fn expression(&mut self, exp: &Expression) -> std::io::Result<String> {
+        match exp.expression_type {
+            ExpressionType::Variable => {
+                let label = format!("var_{}", exp.variable_index()?);
+                Ok(self.node(&label, "ellipse", "solid", exp.folder()))
+            }
+
+            ExpressionType::LiteralValue => {
+                let value = exp.get_literal_value()?;
+                let label = match &value {
+                    Variable::String(Some(value)) => format!("{value:?}"),
+                    Variable::Char(Some(value)) => format!("{value:?}"),
+                    _ => value.to_string(),
+                };
+
+                let label = format!("{label} : {}", value.get_type().as_str());
+                Ok(self.node(&label, "ellipse", "filled", exp.folder()))
+            }
+
+            ExpressionType::Call => {
+                let label = format!("call proc_{}", exp.procedure_index()?);
+                let id = self.node(&label, "ellipse", "solid", exp.folder());
+                self.arguments(exp.arguments_folder(), &id)?;
+
+                Ok(id)
+            }
+
+            ExpressionType::Cast => {
+                let label = format!("Cast to {}", exp.target_type()?.as_str());
+                let id = self.node(&label, "ellipse", "solid", exp.folder());
+
+                let operand = self.expression(&exp.cast_operand()?)?;
+                self.edge(&id, &operand, None);
+
+                Ok(id)
+            }
+
+            ExpressionType::Not => {
+                let id = self.node("Not", "ellipse", "solid", exp.folder());
+
+                let operand = self.expression(&exp.operand()?)?;
+                self.edge(&id, &operand, None);
+
+                Ok(id)
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                let id = self.node(&format!("{exp_type:?}"), "ellipse", "solid", exp.folder());
+
+                let operands = exp.sequence_operands()?;
+                let labels = match exp_type {
+                    ExpressionType::IndexSet => ["array", "index", "value"].as_slice(),
+                    ExpressionType::IndexGet => &["sequence", "index"],
+                    ExpressionType::Push => &["array", "value"],
+                    ExpressionType::Substring => &["string", "start", "end"],
+                    _ => &["sequence"],
+                };
+
+                for (operand, label) in operands.iter().zip(labels) {
+                    let operand = self.expression(operand)?;
+                    self.edge(&id, &operand, Some(label));
+                }
+
+                Ok(id)
+            }
+
+            _ => {
+                let label = format!("{:?}", exp.expression_type);
+                let id = self.node(&label, "ellipse", "solid", exp.folder());
+
+                let (first, second) = exp.operands()?;
+                let first = self.expression(&first)?;
+                let second = self.expression(&second)?;
+
+                self.edge(&id, &first, None);
+                self.edge(&id, &second, None);
+
+                Ok(id)
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    time::Duration,
+};
+
+/// time between two looks at the flag stopping a wait for stdin
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+type Line = std::io::Result<Option<String>>;
+
+/// How 'input' commands turn the lines they read into values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum InputPolicy {
+    /// Int, then Float, then Char if the trimmed line is a single character, String otherwise
+    #[default]
+    Infer,
+
+    /// the line as it is, without its line ending
+    String,
+
+    /// parsed as the type of the declared variable, like a cast from String
+    Typed,
+}
+
+/// Lines read by 'input' commands.
+///
+/// Lines given before running are used instead of stdin, once they are all
+/// read the program gets empty lines, as it would at the end of stdin.
+#[derive(Default)]
+pub struct InputQueue {
+    /// lines given before running, None when reading from stdin
+    lines: Option<RefCell<VecDeque<String>>>,
+
+    /// stdin is read once the lines given before running are all read
+    then_stdin: bool,
+
+    /// every line read so far
+    read: RefCell<Vec<String>>,
+
+    /// line of stdin being read by a background thread, see `wait`
+    waiting: RefCell<Option<Receiver<Line>>>,
+
+    /// line of stdin read while waiting
+    received: RefCell<Option<Line>>,
+
+    /// running out of input is an error instead of giving empty lines
+    strict: bool,
+
+    pub policy: InputPolicy,
+}
+
+impl InputQueue {
+    pub fn new(lines: Option<Vec<String>>, strict: bool, policy: InputPolicy) -> Self {
+        InputQueue {
+            lines: lines.map(|lines| RefCell::new(lines.into())),
+            then_stdin: false,
+            read: RefCell::default(),
+            waiting: RefCell::default(),
+            received: RefCell::default(),
+            strict,
+            policy,
+        }
+    }
+
+    /// Queue giving the lines read so far again, followed by the input left
+    pub fn replay(&self) -> Self {
+        let mut lines = self.read.borrow().clone();
+        lines.extend(self.pending().unwrap_or_default());
+
+        InputQueue {
+            lines: Some(RefCell::new(lines.into())),
+            then_stdin: self.lines.is_none() || self.then_stdin,
+            read: RefCell::default(),
+            waiting: RefCell::default(),
+            received: RefCell::default(),
+            strict: self.strict,
+            policy: self.policy,
+        }
+    }
+
+    /// Lines given before running that were not read yet, None when reading from stdin
+    pub fn pending(&self) -> Option<Vec<String>> {
+        self.lines
+            .as_ref()
+            .map(|lines| lines.borrow().iter().cloned().collect())
+    }
+
+    /// Wait until the next line can be read without blocking, or until `stop`
+    /// is set, in which case it returns false. The line is read from stdin by
+    /// a background thread, the following call to `read_line` gets it
+    pub fn wait(&self, stop: &AtomicBool) -> bool {
+        let from_stdin = match &self.lines {
+            Some(lines) => lines.borrow().is_empty() && self.then_stdin,
+            None => true,
+        };
+        if !from_stdin || self.received.borrow().is_some() {
+            return true;
+        }
+
+        let mut waiting = self.waiting.borrow_mut();
+        let receiver = waiting.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::sync_channel(1);
+            std::thread::spawn(move || sender.send(read_stdin()));
+            receiver
+        });
+
+        loop {
+            match receiver.recv_timeout(WAIT_INTERVAL) {
+                Ok(line) => {
+                    *self.received.borrow_mut() = Some(line);
+                    *waiting = None;
+                    return true;
+                }
+                Err(RecvTimeoutError::Timeout) if stop.load(Ordering::Relaxed) => return false,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    *waiting = None;
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Next line, without its line ending. None when there is no input left
+    /// in strict mode
+    pub fn read_line(&self) -> std::io::Result<Option<String>> {
+        let line = match self
+            .lines
+            .as_ref()
+            .and_then(|lines| lines.borrow_mut().pop_front())
+        {
+            Some(line) => Some(line),
+            None if self.lines.is_none() || self.then_stdin => {
+                match (self.received.take(), self.waiting.take()) {
+                    (Some(line), _) => line?,
+                    (None, Some(waiting)) => waiting.recv().unwrap_or(Ok(None))?,
+                    (None, None) => read_stdin()?,
+                }
+            }
+            None => None,
+        };
+
+        match line {
+            Some(line) => {
+                self.read.borrow_mut().push(line.clone());
+                Ok(Some(line))
+            }
+            None if self.strict => Ok(None),
+            None => Ok(Some(String::new())),
+        }
+    }
+}
+
+/// Next line of stdin without its line ending, None at the end of stdin
+fn read_stdin() -> Line {
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line)? {
+        0 => Ok(None),
+        _ => Ok(Some(line.trim_end_matches(['\n', '\r']).to_owned())),
+    }
+}
@@ -0,0 +1,231 @@
+//! Execution of a program with an explicit stack of blocks.
+//!
+//! The position in the program is the list of blocks being run, each with the
+//! index of its next command, so that it can be saved and restored (see
+//! `snapshot`). Procedures called from expressions run in a nested interpreter.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        procedure,
+    },
+    scope::Scope,
+};
+
+/// Block of commands being run
+pub struct Frame {
+    /// folder holding the commands
+    pub block: String,
+
+    /// sorted command folders, listed when entering the block
+    pub commands: Vec<String>,
+
+    /// index of the next command to run
+    pub next: usize,
+
+    /// 'if', 'while' or 'call' command that entered the block, None for the
+    /// outermost one. The condition of a 'while' command is checked again
+    /// once its block is done
+    pub command: Option<String>,
+
+    /// the block is the body of a procedure called by a 'call' command
+    pub call: bool,
+
+    pub scope: Rc<RefCell<Scope>>,
+}
+
+pub struct Interpreter {
+    /// innermost block last
+    pub frames: Vec<Frame>,
+
+    context: Rc<Context>,
+}
+
+impl Interpreter {
+    /// Interpreter running the commands of `block` in `scope`
+    pub fn new(
+        block: &str,
+        scope: Rc<RefCell<Scope>>,
+        context: &Rc<Context>,
+    ) -> std::io::Result<Self> {
+        let mut interpreter = Interpreter {
+            frames: vec![],
+            context: context.clone(),
+        };
+        interpreter.enter(block, scope, None, false)?;
+
+        Ok(interpreter)
+    }
+
+    /// Interpreter resuming from `frames`
+    pub fn with_frames(frames: Vec<Frame>, context: &Rc<Context>) -> Self {
+        for folder in frames.iter().filter_map(|frame| frame.command.as_deref()) {
+            context.profiler.reopen(folder);
+        }
+
+        Interpreter {
+            frames,
+            context: context.clone(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Run every remaining command
+    pub fn run(&mut self) -> std::io::Result<()> {
+        while !self.is_done() {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    /// The next step runs an 'input' command
+    pub fn reads_input_next(&self) -> std::io::Result<bool> {
+        let Some(frame) = self.frames.last() else {
+            return Ok(false);
+        };
+        let Some(folder) = frame.commands.get(frame.next) else {
+            return Ok(false);
+        };
+        let cmd = Command::new(folder, &frame.scope, &self.context)?;
+
+        Ok(cmd.command_type == CommandType::Input)
+    }
+
+    /// Run the next command, entering a block for 'if', 'while' and 'call'
+    /// commands, or leave the current block once it is done
+    pub fn step(&mut self) -> std::io::Result<()> {
+        let Some(frame) = self.frames.last_mut() else {
+            return Ok(());
+        };
+
+        let Some(folder) = frame.commands.get(frame.next).cloned() else {
+            return self.leave();
+        };
+        frame.next += 1;
+
+        let scope = frame.scope.clone();
+        self.context.profiler.enter(&folder);
+        self.context.coverage.hit(&folder);
+        let mut cmd = Command::new(&folder, &scope, &self.context)?;
+
+        match cmd.command_type {
+            CommandType::If => {
+                let entered = cmd.expression()?.execute()?.is_truthy();
+                self.context.coverage.branch(&folder, entered);
+
+                let block = if entered {
+                    Some(cmd.body_folder()?)
+                } else {
+                    cmd.else_folder()
+                };
+
+                match block {
+                    Some(block) => self.enter(block, new_scope(&scope), Some(folder), false)?,
+                    None => self.context.profiler.exit(),
+                }
+            }
+            CommandType::While => {
+                if cmd.expression()?.execute()?.is_truthy() {
+                    self.context.profiler.iterate(&folder);
+                    self.enter(cmd.body_folder()?, new_scope(&scope), Some(folder), false)?;
+                } else {
+                    self.context.profiler.exit();
+                }
+            }
+            CommandType::Call => {
+                let (body, procedure_scope) = procedure::enter(
+                    cmd.folder(),
+                    cmd.procedure_index()?,
+                    cmd.arguments_folder()?,
+                    &scope,
+                    &self.context,
+                )?;
+                self.enter(&body, procedure_scope, Some(folder), true)?;
+            }
+            _ => {
+                cmd.run()?;
+                self.context.profiler.exit();
+
+                if self.context.is_returning() {
+                    self.unwind();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn enter(
+        &mut self,
+        block: &str,
+        scope: Rc<RefCell<Scope>>,
+        command: Option<String>,
+        call: bool,
+    ) -> std::io::Result<()> {
+        self.frames.push(Frame {
+            block: block.to_owned(),
+            commands: self.context.cache.sorted_subfolders(block)?,
+            next: 0,
+            command,
+            call,
+            scope,
+        });
+
+        Ok(())
+    }
+
+    /// Leave the current block, running the body of a 'while' command again
+    /// while its condition holds
+    fn leave(&mut self) -> std::io::Result<()> {
+        let Some(frame) = self.frames.pop() else {
+            return Ok(());
+        };
+
+        if frame.call {
+            self.context.calls.borrow_mut().pop();
+        }
+
+        let Some(folder) = frame.command else {
+            return Ok(());
+        };
+
+        if let Some(parent) = self.frames.last() {
+            let scope = parent.scope.clone();
+            let cmd = Command::new(&folder, &scope, &self.context)?;
+
+            if cmd.command_type == CommandType::While && cmd.expression()?.execute()?.is_truthy() {
+                self.context.profiler.iterate(&folder);
+                return self.enter(cmd.body_folder()?, new_scope(&scope), Some(folder), false);
+            }
+        }
+
+        self.context.profiler.exit();
+        Ok(())
+    }
+
+    /// Leave every block up to the procedure call a 'return' command ended.
+    /// Calls made from expressions are left by their own interpreter
+    fn unwind(&mut self) {
+        while let Some(frame) = self.frames.pop() {
+            if frame.command.is_some() {
+                self.context.profiler.exit();
+            }
+
+            if frame.call {
+                self.context.calls.borrow_mut().pop();
+                break;
+            }
+        }
+    }
+}
+
+fn new_scope(parent: &Rc<RefCell<Scope>>) -> Rc<RefCell<Scope>> {
+    Rc::new(RefCell::new(Scope::new(Some(parent.clone()))))
+}
@@ -1,11 +1,15 @@
+use crate::analysis::ScopeAnalysis;
+use crate::context::Context;
+use crate::input::InputPolicy;
 use crate::scope::Scope;
 use crate::transpile::Transpile;
 use std::io::Write;
 use std::{cell::RefCell, rc::Rc};
 
-use super::expression::{Expression, ExpressionType};
+use super::expression::Expression;
+use super::procedure::{self, Procedure};
 use super::variable::{Type, Variable};
-use crate::utils::*;
+use crate::utils::input_error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommandType {
@@ -15,23 +19,71 @@ pub enum CommandType {
     Let,
     Print,
     Input,
+
+    /// extension : write the value of an expression as a literal folder
+    Write,
+
+    /// extension : create an empty folder
+    CreateFolder,
+
+    /// extension : remove a folder and its content
+    RemoveFolder,
+
+    /// extension : define a procedure
+    Define,
+
+    /// extension : call a procedure, ignoring its return value
+    Call,
+
+    /// extension : end the current procedure call with a value
+    Return,
+}
+
+impl CommandType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::If => "if",
+            Self::While => "while",
+            Self::Declare => "declare",
+            Self::Let => "let",
+            Self::Print => "print",
+            Self::Input => "input",
+            Self::Write => "write",
+            Self::CreateFolder => "mkdir",
+            Self::RemoveFolder => "rmdir",
+            Self::Define => "define",
+            Self::Call => "call",
+            Self::Return => "return",
+        }
+    }
+
+    /// Commands only available with extensions enabled
+    pub fn is_extension(&self) -> bool {
+        matches!(
+            self,
+            Self::Write
+                | Self::CreateFolder
+                | Self::RemoveFolder
+                | Self::Define
+                | Self::Call
+                | Self::Return
+        )
+    }
 }
 
 pub struct Command {
+    /// folder holding this command
+    folder: String,
+
     /// sorted list of subfolder associated to this command
     folders: Vec<String>,
 
     /// scope that own this command
     scope: Rc<RefCell<Scope>>,
 
-    command_type: CommandType,
-
-    translation_context: Option<TranslationContext>,
-}
+    context: Rc<Context>,
 
-#[derive(Clone, Default)]
-struct TranslationContext {
-    token: String,
+    pub command_type: CommandType,
 }
 
 impl Command {
@@ -43,20 +95,108 @@ impl Command {
             3 => Some(CommandType::Let),
             4 => Some(CommandType::Print),
             5 => Some(CommandType::Input),
+            6 => Some(CommandType::Write),
+            7 => Some(CommandType::CreateFolder),
+            8 => Some(CommandType::RemoveFolder),
+            9 => Some(CommandType::Define),
+            10 => Some(CommandType::Call),
+            11 => Some(CommandType::Return),
+            _ => None,
+        }
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Sorted subfolders of this command
+    pub fn subfolders(&self) -> &[String] {
+        &self.folders
+    }
+
+    /// Index of the variable targeted by a 'declare', 'let' or 'input' command
+    pub fn variable_index(&self) -> std::io::Result<usize> {
+        match self.command_type {
+            CommandType::Declare => self.context.cache.subfolder_count(&self.folders[2]),
+            _ => self.context.cache.subfolder_count(&self.folders[1]),
+        }
+    }
+
+    /// Type of the variable introduced by a 'declare' command
+    pub fn declared_type(&self) -> std::io::Result<Type> {
+        Type::read(&self.folders[1], &self.context)
+    }
+
+    /// Expression evaluated by this command, the targeted path for commands modifying folders
+    pub fn expression(&self) -> std::io::Result<Expression> {
+        match self.command_type {
+            CommandType::Let => Expression::new(&self.folders[2], &self.scope, &self.context),
+            _ => Expression::new(&self.folders[1], &self.scope, &self.context),
+        }
+    }
+
+    /// Value written by a 'write' command
+    pub fn value_expression(&self) -> std::io::Result<Expression> {
+        match self.command_type {
+            CommandType::Write => Expression::new(&self.folders[2], &self.scope, &self.context),
+            _ => Err(input_error(format!(
+                "{} : {:?} command writes no value",
+                self.folder, self.command_type
+            ))),
+        }
+    }
+
+    /// Name of the procedure defined or called, 'proc_{index}'
+    pub fn procedure_index(&self) -> std::io::Result<usize> {
+        self.context.cache.subfolder_count(&self.folders[1])
+    }
+
+    /// Number of parameters of the procedure defined by a 'define' command
+    pub fn parameter_count(&self) -> std::io::Result<usize> {
+        self.context.cache.subfolder_count(&self.folders[2])
+    }
+
+    /// Folder holding the argument expressions of a 'call' command
+    pub fn arguments_folder(&self) -> std::io::Result<&str> {
+        match self.command_type {
+            CommandType::Call => Ok(&self.folders[2]),
+            _ => Err(input_error(format!(
+                "{} : {:?} command has no arguments",
+                self.folder, self.command_type
+            ))),
+        }
+    }
+
+    /// Folder holding the list of commands of an 'if', 'while' or 'define' statement
+    pub fn body_folder(&self) -> std::io::Result<&str> {
+        match self.command_type {
+            CommandType::If | CommandType::While => Ok(&self.folders[2]),
+            CommandType::Define => Ok(&self.folders[3]),
+            _ => Err(input_error(format!(
+                "{} : {:?} command has no body",
+                self.folder, self.command_type
+            ))),
+        }
+    }
+
+    /// Folder holding the commands run when the condition of an 'if' statement is false
+    pub fn else_folder(&self) -> Option<&str> {
+        match self.command_type {
+            CommandType::If => self.folders.get(3).map(|folder| folder.as_str()),
             _ => None,
         }
     }
 
     fn declare_variable(&mut self) -> std::io::Result<()> {
-        let folders_count = subfolder_count(&self.folders[1])?;
-        let index = subfolder_count(&self.folders[2])?;
+        let var_type = self.declared_type()?;
+        let index = self.context.cache.subfolder_count(&self.folders[2])?;
 
         let mut scope = self.scope.borrow_mut();
-        scope.declare_variable(folders_count, index)?;
+        scope.declare_variable_with_type(var_type, index)?;
 
-        if let Some(ctx) = self.translation_context.as_mut() {
-            let var = scope.get_variable(index).unwrap();
-            ctx.token = format!("let mut var_{index}: {};", var.get_type().as_str());
+        // arrays start empty, values are then pushed to them
+        if let Type::Array(element) = var_type {
+            scope.set_variable(index, Variable::Array(*element, Some(vec![])))?;
         }
 
         Ok(())
@@ -64,27 +204,11 @@ impl Command {
 
     /// 'let' instruction
     fn store_expression(&mut self) -> std::io::Result<()> {
-        let var_index = subfolder_count(&self.folders[1])?;
+        let var_index = self.context.cache.subfolder_count(&self.folders[1])?;
 
-        let mut exp = Expression::new(&self.folders[2], &self.scope)?;
+        let exp = self.expression()?;
         let value = exp.execute()?;
 
-        if let Some(ctx) = self.translation_context.as_mut() {
-            let rvalue = exp.transpile()?;
-
-            let scope = self.scope.borrow();
-            if scope.get_variable(var_index).is_none() {
-                ctx.token += "let mut ";
-            }
-
-            if value.get_type() == Type::String && exp.expression_type == ExpressionType::Variable {
-                // make sure to not take ownership of the new string
-                ctx.token += &format!("var_{var_index} = {rvalue}.clone();");
-            } else {
-                ctx.token += &format!("var_{var_index} = {rvalue};");
-            }
-        }
-
         let mut scope = self.scope.borrow_mut();
         scope.set_or_create_variable(var_index, value)?;
 
@@ -92,7 +216,7 @@ impl Command {
     }
 
     fn print_expression(&self) -> std::io::Result<()> {
-        let exp = Expression::new(&self.folders[1], &self.scope)?;
+        let exp = self.expression()?;
         let value = exp.execute()?;
 
         print!("{value}");
@@ -101,37 +225,113 @@ impl Command {
         Ok(())
     }
 
-    fn execute_in_new_scope(&self, parent_folder: &str) -> std::io::Result<()> {
-        let subfolders = sorted_subfolders(parent_folder)?;
-        let scope = Rc::new(RefCell::new(Scope::new(Some(self.scope.clone()))));
+    /// 'write', 'mkdir' and 'rmdir' instructions
+    fn modify_program(&self) -> std::io::Result<()> {
+        let path = match self.expression()?.execute()? {
+            Variable::String(Some(path)) => path,
+            value => {
+                return Err(input_error(format!(
+                    "{} : expected a path as String, found {:?}",
+                    self.folders[1],
+                    value.get_type()
+                )))
+            }
+        };
+        let target = crate::tree::resolve(&self.context.root, &path)
+            .map_err(|err| input_error(format!("{} : {err}", self.folder)))?;
 
-        for folder in subfolders {
-            let mut cmd = Command::new(&folder, &scope)?;
-            cmd.run()?;
+        let result = match self.command_type {
+            CommandType::Write => {
+                let value = self.value_expression()?.execute()?;
+                crate::tree::write_literal(&target, &value)
+            }
+            CommandType::CreateFolder => std::fs::create_dir(&target),
+            _ if target == std::fs::canonicalize(&self.context.root)? => Err(input_error(
+                "the program folder can not be removed".to_owned(),
+            )),
+            _ => std::fs::remove_dir_all(&target),
+        };
+
+        // listings read so far may be outdated
+        self.context.cache.clear();
+
+        result.map_err(|err| input_error(format!("{} : {err}", self.folder)))
+    }
+
+    /// 'define' instruction, defining the same procedure again is only allowed from the same folder
+    fn define_procedure(&self) -> std::io::Result<()> {
+        let index = self.procedure_index()?;
+        let mut procedures = self.context.procedures.borrow_mut();
+
+        if let Some(procedure) = procedures.get(&index) {
+            if procedure.folder != self.folder {
+                return Err(input_error(format!(
+                    "{} : proc_{index} is already defined in {}",
+                    self.folder, procedure.folder
+                )));
+            }
         }
 
+        procedures.insert(
+            index,
+            Procedure {
+                folder: self.folder.clone(),
+                body: self.body_folder()?.to_owned(),
+                parameters: self.parameter_count()?,
+            },
+        );
+
         Ok(())
     }
 
-    fn get_input(&self) -> std::io::Result<Variable> {
-        let mut input = String::new();
+    /// Read the value stored in `var_index`, parsed according to the input policy
+    fn get_input(&self, var_index: usize) -> std::io::Result<Variable> {
+        let Some(input) = self.context.input.read_line()? else {
+            return Err(input_error(format!(
+                "{} : the program asks for more input than provided",
+                self.folder
+            )));
+        };
 
-        std::io::stdin().read_line(&mut input)?;
+        let var = match self.context.input.policy {
+            InputPolicy::Infer => {
+                let input = input.trim();
+                if let Ok(int) = input.parse::<i32>() {
+                    Variable::Int(Some(int))
+                } else if let Ok(float) = input.parse::<f32>() {
+                    Variable::Float(Some(float))
+                } else if input.len() == 1 {
+                    Variable::Char(Some(input.chars().next().unwrap()))
+                } else {
+                    Variable::String(Some(input.to_owned()))
+                }
+            }
+            InputPolicy::String => Variable::String(Some(input)),
+            InputPolicy::Typed => {
+                let Some(declared) = self.scope.borrow().get_variable(var_index) else {
+                    return Err(input_error(format!(
+                        "{} : var_{var_index} must be declared to be read with the typed input policy",
+                        self.folder
+                    )));
+                };
 
-        let input = input.trim();
-        let var = if let Ok(int) = input.parse::<i32>() {
-            Variable::Int(Some(int))
-        } else if let Ok(float) = input.parse::<f32>() {
-            Variable::Float(Some(float))
-        } else if input.len() == 1 {
-            Variable::Char(Some(input.chars().next().unwrap()))
-        } else {
-            Variable::String(Some(input.to_owned()))
+                let to = declared.get_type();
+                Variable::String(Some(input.clone()))
+                    .cast(to)
+                    .ok_or_else(|| {
+                        input_error(format!(
+                            "{} : can not read {input:?} as {}",
+                            self.folder,
+                            to.as_str()
+                        ))
+                    })?
+            }
         };
 
         Ok(var)
     }
 
+    /// Run a command that does not enter a block of commands, see `Interpreter` for the others
     pub fn run(&mut self) -> std::io::Result<()> {
         match self.command_type {
             CommandType::Declare => {
@@ -143,45 +343,53 @@ impl Command {
             CommandType::Print => {
                 self.print_expression()?;
             }
-            CommandType::If => {
-                let exp = Expression::new(&self.folders[1], &self.scope)?;
-                let value = exp.execute()?;
-
-                if value.is_truthy() {
-                    self.execute_in_new_scope(&self.folders[2])?;
-                }
-            }
-            CommandType::While => loop {
-                let exp = Expression::new(&self.folders[1], &self.scope)?;
-                let value = exp.execute()?;
-
-                if value.is_truthy() {
-                    self.execute_in_new_scope(&self.folders[2])?;
-                } else {
-                    break;
-                }
-            },
             CommandType::Input => {
-                let value = self.get_input()?;
-                let var_index = subfolder_count(&self.folders[1])?;
+                let var_index = self.context.cache.subfolder_count(&self.folders[1])?;
+                let value = self.get_input(var_index)?;
 
                 let mut scope = self.scope.borrow_mut();
                 scope.set_or_create_variable(var_index, value)?;
             }
+            CommandType::Write | CommandType::CreateFolder | CommandType::RemoveFolder => {
+                self.modify_program()?;
+            }
+            CommandType::Define => {
+                self.define_procedure()?;
+            }
+            CommandType::If | CommandType::While | CommandType::Call => {
+                unreachable!("blocks are entered by the interpreter")
+            }
+            CommandType::Return => {
+                let value = self.expression()?.execute()?;
+
+                match self.context.calls.borrow_mut().last_mut() {
+                    Some(call) => *call = Some(value),
+                    None => {
+                        return Err(input_error(format!(
+                            "{} : 'return' outside of a procedure",
+                            self.folder
+                        )))
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    pub fn new(folder: &str, scope: &Rc<RefCell<Scope>>) -> std::io::Result<Self> {
-        let subfolders = sorted_subfolders(folder)?;
+    pub fn new(
+        folder: &str,
+        scope: &Rc<RefCell<Scope>>,
+        context: &Rc<Context>,
+    ) -> std::io::Result<Self> {
+        let subfolders = context.cache.sorted_subfolders(folder)?;
         if subfolders.is_empty() {
             return Err(input_error(format!(
                 "{folder}: invalid command, folder is empty"
             )));
         }
 
-        let count = subfolder_count(&subfolders[0])?;
+        let count = context.cache.subfolder_count(&subfolders[0])?;
         let command_type = Command::get_type(count);
 
         if command_type.is_none() {
@@ -195,145 +403,257 @@ impl Command {
         }
         let command_type = command_type.unwrap();
 
+        if command_type.is_extension() && !context.extensions {
+            return Err(input_error(format!(
+                "{folder} : '{}' command is an extension, run with --extensions",
+                command_type.as_str()
+            )));
+        }
+
         let folder_count = subfolders.len();
-        if command_type == CommandType::Input || command_type == CommandType::Print {
+        if matches!(
+            command_type,
+            CommandType::Input
+                | CommandType::Print
+                | CommandType::CreateFolder
+                | CommandType::RemoveFolder
+                | CommandType::Return
+        ) {
             if folder_count != 2 {
                 return Err(input_error(format!(
                     "{folder} : expected 2 folders, {folder_count} found"
                 )));
             }
-        } else {
-            if folder_count != 3 {
+        } else if command_type == CommandType::Define {
+            if folder_count != 4 {
                 return Err(input_error(format!(
-                    "{folder} : expected 3 folders, {folder_count} found"
+                    "{folder} : expected 4 folders, {folder_count} found"
                 )));
             }
+        } else if command_type == CommandType::If && folder_count == 4 {
+            if !context.extensions {
+                return Err(input_error(format!(
+                    "{folder} : 'else' body is an extension, run with --extensions"
+                )));
+            }
+        } else if folder_count != 3 {
+            return Err(input_error(format!(
+                "{folder} : expected 3 folders, {folder_count} found"
+            )));
         }
 
         let cmd = Command {
             command_type,
+            folder: folder.to_owned(),
             folders: subfolders,
             scope: scope.clone(),
-            translation_context: None,
+            context: context.clone(),
         };
 
         Ok(cmd)
     }
 }
 
-impl Transpile for Command {
-    fn transpile(&mut self) -> std::io::Result<String> {
-        self.translation_context = Some(TranslationContext::default());
-        let ctx = self.translation_context.as_mut().unwrap();
+impl Command {
+    /// Condition of an 'if' or 'while' statement as a Rust boolean
+    fn transpile_condition(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        self.expression()?.transpile_condition(analysis)
+    }
 
-        match self.command_type {
-            CommandType::Declare => {
-                self.declare_variable()?;
+    /// Commands of a body folder, indented
+    fn transpile_body(&self, folder: &str, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        let mut token = String::new();
+
+        for folder in self.context.cache.sorted_subfolders(folder)? {
+            let cmd = Command::new(&folder, &self.scope, &self.context)?;
+            if self.context.optimizations.is_dead(cmd.folder()) {
+                continue;
             }
 
-            CommandType::Let => {
-                self.store_expression()?;
+            for line in cmd.transpile(analysis)?.split('\n') {
+                token += &format!("\t{line}\n");
             }
+        }
 
-            CommandType::If => {
-                let mut exp = Expression::new(&self.folders[1], &self.scope)?;
-                let token = exp.transpile()?;
-                let value = exp.execute()?;
-
-                let token = match exp.expression_type {
-                    ExpressionType::EqualTo
-                    | ExpressionType::GreaterThan
-                    | ExpressionType::LessThan => token,
-                    _ => match value.get_type() {
-                        Type::Char => format!("{token} != '\0'"),
-                        Type::Float => format!("{token} != 0.0"),
-                        Type::Int => format!("{token} != 0"),
-                        Type::String => {
-                            if exp.expression_type == ExpressionType::Add {
-                                format!("!({token}).is_empty()")
-                            } else {
-                                format!("!{token}.is_empty()")
-                            }
-                        }
-                    },
-                };
+        Ok(token)
+    }
+}
 
-                ctx.token = format!("\nif {token} {{\n");
+impl Command {
+    /// Rust function of a 'define' command, None if the procedure is never called
+    pub fn transpile_procedure(&self, analysis: &ScopeAnalysis) -> std::io::Result<Option<String>> {
+        let signature = match analysis.signature(&self.folder) {
+            Some(signature) => signature,
+            None => return Ok(None),
+        };
 
-                let subfolders = sorted_subfolders(&self.folders[2])?;
-                let scope = Rc::new(RefCell::new(Scope::new(Some(self.scope.clone()))));
+        let parameters: Vec<String> = signature
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(i, parameter)| format!("mut var_{i}: {}", parameter.as_str()))
+            .collect();
 
-                for folder in subfolders {
-                    let mut cmd = Command::new(&folder, &scope)?;
+        let mut token = format!("fn proc_{}({})", signature.index, parameters.join(", "));
+        if let Some(return_type) = signature.return_type {
+            token += &format!(" -> {}", return_type.as_str());
+        }
+        token += " {\n";
+        token += &self.transpile_body(self.body_folder()?, analysis)?;
+
+        if signature.return_type.is_some() {
+            token += &format!(
+                "\tunreachable!(\"proc_{} did not return a value\")\n",
+                signature.index
+            );
+        }
+        token += "}\n";
 
-                    for line in cmd.transpile()?.split("\n") {
-                        ctx.token += &format!("\t{line}\n");
-                    }
-                }
+        Ok(Some(token))
+    }
+}
 
-                ctx.token += "}";
-            }
+impl Transpile for Command {
+    fn transpile(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        let mut token = String::new();
 
-            CommandType::While => {
-                let mut exp = Expression::new(&self.folders[1], &self.scope)?;
-                let token = exp.transpile()?;
-                let value = exp.execute()?;
-
-                let token = match exp.expression_type {
-                    ExpressionType::EqualTo
-                    | ExpressionType::GreaterThan
-                    | ExpressionType::LessThan => token,
-                    _ => match value.get_type() {
-                        Type::Char => format!("{token} != '\0'"),
-                        Type::Float => format!("{token} != 0.0"),
-                        Type::Int => format!("{token} != 0"),
-                        Type::String => {
-                            if exp.expression_type == ExpressionType::Add {
-                                format!("!({token}).is_empty()")
-                            } else {
-                                format!("!{token}.is_empty()")
-                            }
-                        }
-                    },
+        match self.command_type {
+            CommandType::Declare => {
+                let binding = analysis.binding(&self.folder)?;
+                let var_type = binding.var_type;
+
+                // initialized so that Rust accepts reads guarded by runtime conditions
+                let default = match var_type {
+                    Type::Char => "'\\0'",
+                    Type::Float => "0.0",
+                    Type::Int => "0",
+                    Type::String => "String::new()",
+                    Type::Bool => "false",
+                    Type::Array(_) => "Vec::new()",
                 };
 
-                ctx.token = format!("\nwhile {token} {{\n");
+                token = format!(
+                    "let mut var_{}: {} = {default};",
+                    binding.index,
+                    var_type.as_str()
+                );
+            }
 
-                let subfolders = sorted_subfolders(&self.folders[2])?;
-                let scope = Rc::new(RefCell::new(Scope::new(Some(self.scope.clone()))));
+            CommandType::Let => {
+                let binding = analysis.binding(&self.folder)?;
+                // make sure to not take ownership of the new string or array
+                let rvalue = self.expression()?.transpile_owned(analysis)?;
+
+                if binding.declares {
+                    token += &format!(
+                        "let mut var_{}: {} = {rvalue};",
+                        binding.index,
+                        binding.var_type.as_str()
+                    );
+                } else {
+                    token += &format!("var_{} = {rvalue};", binding.index);
+                }
+            }
 
-                for folder in subfolders {
-                    let mut cmd = Command::new(&folder, &scope)?;
+            CommandType::If => {
+                let body = self.body_folder()?;
+
+                match (
+                    self.context.optimizations.constant_condition(&self.folder),
+                    self.else_folder(),
+                ) {
+                    (Some(true), _) => {
+                        token = "\n{\n".to_owned();
+                        token += &self.transpile_body(body, analysis)?;
+                    }
+                    (Some(false), Some(else_folder)) => {
+                        token = "\n{\n".to_owned();
+                        token += &self.transpile_body(else_folder, analysis)?;
+                    }
+                    (_, else_folder) => {
+                        token = format!("\nif {} {{\n", self.transpile_condition(analysis)?);
+                        token += &self.transpile_body(body, analysis)?;
 
-                    for line in cmd.transpile()?.split("\n") {
-                        ctx.token += &format!("\t{line}\n");
+                        if let Some(else_folder) = else_folder {
+                            token += "} else {\n";
+                            token += &self.transpile_body(else_folder, analysis)?;
+                        }
                     }
                 }
+                token += "}";
+            }
 
-                ctx.token += "}";
+            CommandType::While => {
+                token = match self.context.optimizations.constant_condition(&self.folder) {
+                    Some(true) => "\nloop {\n".to_owned(),
+                    _ => format!("\nwhile {} {{\n", self.transpile_condition(analysis)?),
+                };
+                token += &self.transpile_body(self.body_folder()?, analysis)?;
+                token += "}";
             }
 
             CommandType::Print => {
-                let mut exp = Expression::new(&self.folders[1], &self.scope)?;
-                let token = exp.transpile()?;
+                let exp = self.expression()?;
+                let mut value = exp.transpile(analysis)?;
+
+                // same layout as the interpreter, '[a, b]'
+                if let Type::Array(_) = analysis.type_of(exp.folder())? {
+                    value = format!(
+                        "format!(\"[{{}}]\", ({value}).iter().map(|value| value.to_string()).collect::<Vec<String>>().join(\", \"))"
+                    );
+                }
 
-                ctx.token += &format!("print!(\"{{}}\", {token});\n");
-                ctx.token += "std::io::stdout().flush().unwrap();\n";
+                token += &format!("print!(\"{{}}\", {value});\n");
+                token += "std::io::stdout().flush().unwrap();\n";
             }
 
             CommandType::Input => {
-                let var_index = subfolder_count(&self.folders[1])?;
-                let mut scope = self.scope.borrow_mut();
-
-                if scope.get_variable(var_index).is_none() {
-                  ctx.token += &format!("let mut var_{var_index} = String::new();\n");
+                let binding = analysis.binding(&self.folder)?;
+
+                if binding.declares {
+                    token += &format!(
+                        "let mut var_{}: {} = {{\n",
+                        binding.index,
+                        binding.var_type.as_str()
+                    );
+                } else {
+                    token += &format!("var_{} = {{\n", binding.index);
                 }
-                ctx.token += &format!("std::io::stdin().readline(&mut var_{var_index}).unwrap();\n");
+                token += "\tlet mut input = String::new();\n";
+                token += "\tstd::io::stdin().read_line(&mut input).unwrap();\n";
+                token += "\tinput.trim().parse().unwrap()\n";
+                token += "};\n";
+            }
+
+            // procedures are transpiled to functions next to 'main'
+            CommandType::Define => {}
+
+            CommandType::Call => {
+                token = format!(
+                    "proc_{}({});",
+                    self.procedure_index()?,
+                    procedure::transpile_arguments(
+                        self.arguments_folder()?,
+                        &self.scope,
+                        &self.context,
+                        analysis
+                    )?
+                );
+            }
 
-                scope.set_or_create_variable(var_index, Variable::String(Some(String::new())))?;
+            CommandType::Return => {
+                token = format!("return {};", self.expression()?.transpile(analysis)?);
+            }
+
+            CommandType::Write | CommandType::CreateFolder | CommandType::RemoveFolder => {
+                return Err(input_error(format!(
+                    "{} : '{}' command modifies the program folders, it can not be transpiled",
+                    self.folder,
+                    self.command_type.as_str()
+                )));
             }
         }
 
-        Ok(self.translation_context.clone().unwrap().token)
+        Ok(token)
     }
 }
@@ -1,9 +1,15 @@
 use crate::{
-    items::variable::Type, scope::Scope, transpile::Transpile, utils::{get_byte, input_error, sorted_subfolders, subfolder_count}
+    analysis::{binary_type, ScopeAnalysis},
+    context::Context,
+    items::variable::Type,
+    numeric::NumericMode,
+    scope::Scope,
+    transpile::Transpile,
+    utils::input_error,
 };
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, rc::Rc};
 
-use super::variable::Variable;
+use super::{procedure, variable::Variable};
 
 #[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExpressionType {
@@ -16,16 +22,107 @@ pub enum ExpressionType {
     EqualTo,
     GreaterThan,
     LessThan,
+
+    // extensions
+    Modulo,
+    NotEqualTo,
+    And,
+    Or,
+    Not,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    Call,
+    IndexGet,
+    IndexSet,
+    Length,
+    Push,
+    Cast,
+    Substring,
+    IntDivide,
+}
+
+impl ExpressionType {
+    /// Expressions only available with extensions enabled
+    pub fn is_extension(&self) -> bool {
+        !matches!(
+            self,
+            Self::Variable
+                | Self::Add
+                | Self::Substract
+                | Self::Multiply
+                | Self::Divide
+                | Self::LiteralValue
+                | Self::EqualTo
+                | Self::GreaterThan
+                | Self::LessThan
+        )
+    }
+
+    /// Expressions giving a Rust bool once transpiled
+    pub fn is_boolean(&self) -> bool {
+        matches!(
+            self,
+            Self::EqualTo
+                | Self::GreaterThan
+                | Self::LessThan
+                | Self::NotEqualTo
+                | Self::And
+                | Self::Or
+                | Self::Not
+        )
+    }
+
+    /// Expressions ordering their operands
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::EqualTo | Self::GreaterThan | Self::LessThan | Self::NotEqualTo
+        )
+    }
+
+    /// Expressions working on the bits of Ints
+    pub fn is_bitwise(&self) -> bool {
+        matches!(
+            self,
+            Self::BitAnd | Self::BitOr | Self::BitXor | Self::ShiftLeft | Self::ShiftRight
+        )
+    }
+
+    /// Expressions reading or building arrays, some also read strings
+    pub fn is_sequence(&self) -> bool {
+        matches!(
+            self,
+            Self::IndexGet | Self::IndexSet | Self::Length | Self::Push | Self::Substring
+        )
+    }
+
+    /// Number of folders of expressions whose operands are not a pair
+    fn folder_count(&self) -> Option<usize> {
+        match self {
+            Self::Not | Self::Length => Some(2),
+            Self::Call | Self::IndexGet | Self::Push | Self::Cast => Some(3),
+            Self::IndexSet | Self::Substring => Some(4),
+            _ => None,
+        }
+    }
 }
 
 pub struct Expression {
     pub expression_type: ExpressionType,
 
+    /// folder holding this expression
+    folder: String,
+
     /// sorted list of folders associated to this expression
     folders: Vec<String>,
 
     /// scope that own this expression
     scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
 }
 
 impl Expression {
@@ -40,89 +137,399 @@ impl Expression {
             6 => Some(ExpressionType::EqualTo),
             7 => Some(ExpressionType::GreaterThan),
             8 => Some(ExpressionType::LessThan),
+            9 => Some(ExpressionType::Modulo),
+            10 => Some(ExpressionType::NotEqualTo),
+            11 => Some(ExpressionType::And),
+            12 => Some(ExpressionType::Or),
+            13 => Some(ExpressionType::Not),
+            14 => Some(ExpressionType::BitAnd),
+            15 => Some(ExpressionType::BitOr),
+            16 => Some(ExpressionType::BitXor),
+            17 => Some(ExpressionType::ShiftLeft),
+            18 => Some(ExpressionType::ShiftRight),
+            19 => Some(ExpressionType::Call),
+            20 => Some(ExpressionType::IndexGet),
+            21 => Some(ExpressionType::IndexSet),
+            22 => Some(ExpressionType::Length),
+            23 => Some(ExpressionType::Push),
+            24 => Some(ExpressionType::Cast),
+            25 => Some(ExpressionType::Substring),
+            26 => Some(ExpressionType::IntDivide),
             _ => None,
         }
     }
 
-    fn get_literal_value(&self) -> std::io::Result<Variable> {
-        let var_type = Type::from(subfolder_count(&self.folders[1])?);
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Sorted subfolders of this expression
+    pub fn subfolders(&self) -> &[String] {
+        &self.folders
+    }
 
-        let value_folders = sorted_subfolders(&self.folders[2])?;
+    /// Index of the variable read by a 'Variable' expression
+    pub fn variable_index(&self) -> std::io::Result<usize> {
+        self.context.cache.subfolder_count(&self.folders[1])
+    }
+
+    /// Left and right hand side of a binary expression
+    pub fn operands(&self) -> std::io::Result<(Expression, Expression)> {
+        let first = Expression::new(&self.folders[1], &self.scope, &self.context)?;
+        let second = Expression::new(&self.folders[2], &self.scope, &self.context)?;
+
+        Ok((first, second))
+    }
+
+    /// Name of the procedure called, 'proc_{index}'
+    pub fn procedure_index(&self) -> std::io::Result<usize> {
+        self.context.cache.subfolder_count(&self.folders[1])
+    }
+
+    /// Folder holding the argument expressions of a call
+    pub fn arguments_folder(&self) -> &str {
+        &self.folders[2]
+    }
+
+    /// Operand of a unary expression
+    pub fn operand(&self) -> std::io::Result<Expression> {
+        Expression::new(&self.folders[1], &self.scope, &self.context)
+    }
+
+    /// Type a 'cast' expression converts to
+    pub fn target_type(&self) -> std::io::Result<Type> {
+        Type::read(&self.folders[1], &self.context)
+    }
+
+    /// Expression converted by a 'cast' expression
+    pub fn cast_operand(&self) -> std::io::Result<Expression> {
+        Expression::new(&self.folders[2], &self.scope, &self.context)
+    }
+
+    /// Operands of an array or string expression, the sequence comes first
+    pub fn sequence_operands(&self) -> std::io::Result<Vec<Expression>> {
+        self.folders[1..]
+            .iter()
+            .map(|folder| Expression::new(folder, &self.scope, &self.context))
+            .collect()
+    }
+
+    pub fn get_literal_value(&self) -> std::io::Result<Variable> {
+        let var_type = Type::read(&self.folders[1], &self.context)?;
+
+        let value_folders = self.context.cache.sorted_subfolders(&self.folders[2])?;
         match var_type {
-            Type::Int | Type::Float => {
-                if value_folders.len() > 4 {
-                    return Err(input_error(format!(
-                        "{} : invalid literal value, Int and Float are 32-bit but found {} subfolders",
-                        self.folders[2],
-                        value_folders.len()
-                    )));
-                }
+            Type::Int | Type::Float if value_folders.len() > 4 => {
+                return Err(input_error(format!(
+                    "{} : invalid literal value, Int and Float are 32-bit but found {} subfolders",
+                    self.folders[2],
+                    value_folders.len()
+                )));
             }
-            Type::Char => {
-                if value_folders.len() > 1 {
-                    return Err(input_error(format!(
-                        "{} : invalid literal value, Char should be one byte unicode but found {} subfolders", 
-                        self.folders[2], 
-                        value_folders.len()
-                    )));
-                }
+            Type::Char if value_folders.len() > 1 => {
+                return Err(input_error(format!(
+                    "{} : invalid literal value, Char should be one byte unicode but found {} subfolders",
+                    self.folders[2],
+                    value_folders.len()
+                )));
+            }
+            Type::Bool if value_folders.len() > 1 => {
+                return Err(input_error(format!(
+                    "{} : invalid literal value, Bool should be one byte but found {} subfolders",
+                    self.folders[2],
+                    value_folders.len()
+                )));
+            }
+            Type::Array(_) => {
+                return Err(input_error(format!(
+                    "{} : arrays have no literal value",
+                    self.folders[1]
+                )));
             }
             _ => {}
         }
 
         let mut value: Vec<u8> = vec![];
-        
+
         for folder in value_folders {
-            let byte = get_byte(&folder)?;
+            let byte = self.context.cache.get_byte(&folder)?;
             value.push(byte);
         }
 
         let var = match var_type {
             Type::Int => {
                 let mut bytes: [u8; 4] = [0; 4];
-                for i in 0..value.len() {
-                    bytes[i] = value[i];
-                }
+                bytes[..value.len()].copy_from_slice(&value);
 
                 Variable::Int(Some(i32::from_ne_bytes(bytes)))
             }
 
             Type::Float => {
                 let mut bytes: [u8; 4] = [0; 4];
-                for i in 0..value.len() {
-                    bytes[i] = value[i];
-                }
+                bytes[..value.len()].copy_from_slice(&value);
 
                 Variable::Float(Some(f32::from_ne_bytes(bytes)))
             }
 
-            Type::Char => {
-                Variable::Char(Some(char::from(value[0])))
-            }
+            Type::Char => Variable::Char(Some(char::from(value[0]))),
 
             Type::String => {
                 let mut str_value = String::new();
                 for byte in value {
                     str_value.push(char::from(byte));
                 }
-                
+
                 Variable::String(Some(str_value))
             }
+
+            // any bit set is true
+            Type::Bool => Variable::Bool(Some(value.iter().any(|byte| *byte != 0))),
+
+            Type::Array(_) => unreachable!(),
         };
 
         Ok(var)
     }
 
+    /// Apply a binary operator
+    pub fn evaluate(&self, a: Variable, b: Variable) -> std::io::Result<Variable> {
+        let truth = |value: bool| self.context.truth(value);
+
+        if binary_type(
+            self.expression_type,
+            a.get_type(),
+            b.get_type(),
+            &self.context,
+        )
+        .is_none()
+        {
+            let (a_type, b_type) = (a.get_type(), b.get_type());
+            let message = match self.expression_type {
+                operator if operator.is_comparison() => {
+                    Some(format!("can not compare {a_type:?} and {b_type:?}"))
+                }
+                operator if operator.is_bitwise() => Some(format!(
+                    "bitwise operations only apply to Ints, found {a_type:?} and {b_type:?}"
+                )),
+                ExpressionType::Modulo => Some(format!(
+                    "modulo only applies to Ints and Floats, found {a_type:?} and {b_type:?}"
+                )),
+                _ => None,
+            };
+
+            if let Some(message) = message {
+                return Err(input_error(format!("{} : {message}", self.folder)));
+            }
+        }
+
+        let mode = self.context.numeric;
+        match (self.expression_type, &a, &b) {
+            (operator, Variable::Int(Some(a)), Variable::Int(Some(b)))
+                if NumericMode::applies_to(operator)
+                    && (operator != ExpressionType::IntDivide || *b != 0) =>
+            {
+                return match mode.apply(operator, *a, *b) {
+                    Some(value) => Ok(Variable::Int(Some(value))),
+                    None => Err(input_error(format!(
+                        "{} : {operator:?} of {a} and {b} overflows an Int",
+                        self.folder
+                    ))),
+                };
+            }
+
+            (ExpressionType::Modulo, Variable::Int(Some(_)), Variable::Int(Some(0))) => {
+                return Err(input_error(format!("{} : modulo by zero", self.folder)));
+            }
+
+            (ExpressionType::IntDivide, Variable::Int(Some(_)), Variable::Int(Some(0))) => {
+                return Err(input_error(format!("{} : division by zero", self.folder)));
+            }
+
+            (ExpressionType::IntDivide, _, _) => {
+                return Err(input_error(format!(
+                    "{} : integer division only applies to Ints, found {:?} and {:?}",
+                    self.folder,
+                    a.get_type(),
+                    b.get_type()
+                )));
+            }
+
+            (
+                ExpressionType::Divide | ExpressionType::Modulo,
+                _,
+                Variable::Int(Some(0)) | Variable::Float(Some(0.0)),
+            ) if mode == NumericMode::Checked => {
+                return Err(input_error(format!("{} : division by zero", self.folder)));
+            }
+
+            _ => {}
+        }
+
+        let value = match self.expression_type {
+            ExpressionType::Add => a + b,
+
+            ExpressionType::Substract => a - b,
+
+            ExpressionType::Multiply => a * b,
+
+            ExpressionType::Divide => a / b,
+
+            ExpressionType::EqualTo => truth(a.compare(&b) == Some(Ordering::Equal)),
+
+            ExpressionType::GreaterThan => truth(a.compare(&b) == Some(Ordering::Greater)),
+
+            ExpressionType::LessThan => truth(a.compare(&b) == Some(Ordering::Less)),
+
+            ExpressionType::Modulo => a % b,
+
+            ExpressionType::NotEqualTo => truth(a.compare(&b) != Some(Ordering::Equal)),
+
+            ExpressionType::And => truth(a.is_truthy() && b.is_truthy()),
+
+            ExpressionType::Or => truth(a.is_truthy() || b.is_truthy()),
+
+            ExpressionType::BitAnd => a & b,
+
+            ExpressionType::BitOr => a | b,
+
+            ExpressionType::BitXor => a ^ b,
+
+            ExpressionType::ShiftLeft => a << b,
+
+            ExpressionType::ShiftRight => a >> b,
+
+            _ => Variable::Int(None),
+        };
+
+        Ok(value)
+    }
+
+    /// Apply a unary operator
+    pub fn evaluate_unary(&self, a: Variable) -> Variable {
+        match self.expression_type {
+            ExpressionType::Not => self.context.truth(a.is_falsy()),
+
+            _ => Variable::Int(None),
+        }
+    }
+
+    /// Apply an array or string operator, `values` holding the sequence first
+    pub fn evaluate_sequence(&self, values: Vec<Variable>) -> std::io::Result<Variable> {
+        let mut values = values.into_iter();
+
+        // strings are read as arrays of chars
+        let (sequence_type, element, mut array) = match values.next() {
+            Some(Variable::Array(element, Some(array))) => ("Array", element, array),
+            Some(Variable::String(Some(value)))
+                if !matches!(
+                    self.expression_type,
+                    ExpressionType::IndexSet | ExpressionType::Push
+                ) =>
+            {
+                let chars = value.chars().map(|c| Variable::Char(Some(c))).collect();
+                ("String", Type::Char, chars)
+            }
+            Some(value) => {
+                return Err(input_error(format!(
+                    "{} : can not apply {:?} to {:?}",
+                    self.folders[1],
+                    self.expression_type,
+                    value.get_type()
+                )))
+            }
+            None => unreachable!(),
+        };
+
+        // indexes up to `bound` excluded
+        let length = array.len();
+        let mut index = |folder: &str, bound: usize| match values.next() {
+            Some(Variable::Int(Some(index))) if index >= 0 && (index as usize) < bound => {
+                Ok(index as usize)
+            }
+            Some(Variable::Int(Some(index))) => Err(input_error(format!(
+                "{folder} : index {index} is out of bounds for a {sequence_type} of length {length}"
+            ))),
+            Some(value) => Err(input_error(format!(
+                "{folder} : expected an index as Int, found {:?}",
+                value.get_type()
+            ))),
+            None => unreachable!(),
+        };
+
+        let value = match self.expression_type {
+            ExpressionType::IndexGet => return Ok(array[index(&self.folders[2], length)?].clone()),
+            ExpressionType::Length => return Ok(Variable::Int(Some(length as i32))),
+            ExpressionType::Substring => {
+                let start = index(&self.folders[2], length + 1)?;
+                let end = index(&self.folders[3], length + 1)?;
+                if end < start {
+                    return Err(input_error(format!(
+                        "{} : substring ends at {end}, before its start {start}",
+                        self.folder
+                    )));
+                }
+
+                let value = array[start..end].iter().map(|c| c.to_string()).collect();
+                return Ok(Variable::String(Some(value)));
+            }
+            ExpressionType::IndexSet => Some(index(&self.folders[2], length)?),
+            _ => None,
+        };
+
+        let value_folder = self.folders.last().unwrap();
+        let item = values.next().unwrap();
+        if item.get_type() != element {
+            return Err(input_error(format!(
+                "{value_folder} : can not store {:?} in an Array of {element:?}",
+                item.get_type()
+            )));
+        }
+
+        match value {
+            Some(index) => array[index] = item,
+            None => array.push(item),
+        }
+
+        Ok(Variable::Array(element, Some(array)))
+    }
+
+    /// Apply a 'cast' expression
+    pub fn evaluate_cast(&self, value: Variable) -> std::io::Result<Variable> {
+        let target = self.target_type()?;
+
+        value.cast(target).ok_or_else(|| {
+            input_error(format!(
+                "{} : can not cast {:?} {value} to {target:?}",
+                self.folder,
+                value.get_type()
+            ))
+        })
+    }
+
     pub fn execute(&self) -> std::io::Result<Variable> {
-        // expression does not have to mutate its scope
-        let scope = self.scope.borrow();
+        self.context.profiler.enter(&self.folder);
+        let value = self.compute();
+        self.context.profiler.exit();
+
+        value
+    }
+
+    fn compute(&self) -> std::io::Result<Variable> {
+        if let Some(value) = self.context.optimizations.folded(&self.folder) {
+            return Ok(value.clone());
+        }
 
         if self.expression_type == ExpressionType::Variable {
-            let folder_count = subfolder_count(&self.folders[1])?;
+            let folder_count = self.context.cache.subfolder_count(&self.folders[1])?;
 
-            if let Some(var) = scope.get_variable(folder_count) {
+            // expression does not have to mutate its scope, procedure calls may do
+            // so the scope is only borrowed here
+            if let Some(var) = self.scope.borrow().get_variable(folder_count) {
                 if var.is_null() {
-                    Err(input_error(format!("{} : use of uninitialized variable var_{folder_count}", self.folders[1])))
+                    Err(input_error(format!(
+                        "{} : use of uninitialized variable var_{folder_count}",
+                        self.folders[1]
+                    )))
                 } else {
                     Ok(var)
                 }
@@ -134,42 +541,58 @@ impl Expression {
             }
         } else if self.expression_type == ExpressionType::LiteralValue {
             self.get_literal_value()
+        } else if self.expression_type == ExpressionType::Call {
+            let index = self.procedure_index()?;
+            procedure::call(
+                &self.folder,
+                index,
+                self.arguments_folder(),
+                &self.scope,
+                &self.context,
+            )?
+            .ok_or_else(|| {
+                input_error(format!(
+                    "{} : proc_{index} did not return a value",
+                    self.folder
+                ))
+            })
+        } else if self.expression_type.is_sequence() {
+            let values = self
+                .sequence_operands()?
+                .iter()
+                .map(|operand| operand.execute())
+                .collect::<std::io::Result<Vec<Variable>>>()?;
+
+            self.evaluate_sequence(values)
+        } else if self.expression_type == ExpressionType::Cast {
+            self.evaluate_cast(self.cast_operand()?.execute()?)
+        } else if self.expression_type == ExpressionType::Not {
+            let a = self.operand()?.execute()?;
+
+            Ok(self.evaluate_unary(a))
         } else {
-            let first = Expression::new(&self.folders[1], &self.scope)?;
-            let second = Expression::new(&self.folders[2], &self.scope)?;
+            let (first, second) = self.operands()?;
 
             let a = first.execute()?;
             let b = second.execute()?;
 
-            match self.expression_type {
-                ExpressionType::Add => Ok(a + b),
-
-                ExpressionType::Substract => Ok(a - b),
-
-                ExpressionType::Multiply => Ok(a * b),
-
-                ExpressionType::Divide => Ok(a / b),
-
-                ExpressionType::EqualTo => Ok(Variable::Int(Some((a == b) as i32))),
-
-                ExpressionType::GreaterThan => Ok(Variable::Int(Some((a > b) as i32))),
-
-                ExpressionType::LessThan => Ok(Variable::Int(Some((a < b) as i32))),
-
-                _ => Ok(Variable::Int(None)),
-            }
+            self.evaluate(a, b)
         }
     }
 
-    pub fn new(folder: &str, scope: &Rc<RefCell<Scope>>) -> std::io::Result<Self> {
-        let subfolders = sorted_subfolders(&folder)?;
+    pub fn new(
+        folder: &str,
+        scope: &Rc<RefCell<Scope>>,
+        context: &Rc<Context>,
+    ) -> std::io::Result<Self> {
+        let subfolders = context.cache.sorted_subfolders(folder)?;
         if subfolders.is_empty() {
             return Err(input_error(format!(
                 "{folder} : invalid expression, empty folder"
             )));
         }
 
-        let folder_count = subfolder_count(&subfolders[0])?;
+        let folder_count = context.cache.subfolder_count(&subfolders[0])?;
         let expression_type = Expression::get_type(folder_count);
         if expression_type.is_none() {
             return Err(input_error(format!(
@@ -177,83 +600,364 @@ impl Expression {
             )));
         }
 
+        let expression_type = expression_type.unwrap();
+
+        if expression_type.is_extension() && !context.extensions {
+            return Err(input_error(format!(
+                "{folder} : {expression_type:?} expression is an extension, run with --extensions"
+            )));
+        }
+
+        if let Some(count) = expression_type.folder_count() {
+            if subfolders.len() != count {
+                return Err(input_error(format!(
+                    "{folder} : expected {count} folders, {} found",
+                    subfolders.len()
+                )));
+            }
+        }
+
         Ok(Expression {
-            expression_type: expression_type.unwrap(),
+            expression_type,
+            folder: folder.to_owned(),
             folders: subfolders,
             scope: scope.clone(),
+            context: context.clone(),
         })
     }
 }
 
+/// Rust literal of a value
+fn transpile_value(value: &Variable) -> String {
+    match value.clone() {
+        Variable::Char(value) => format!("{:?}", value.unwrap()),
+        Variable::Float(value) => {
+            let value = value.unwrap();
+            if value.is_nan() {
+                "f32::NAN".to_owned()
+            } else if value.is_infinite() {
+                format!("{}f32::INFINITY", if value < 0.0 { "-" } else { "" })
+            } else {
+                // debug format keeps a decimal point or an exponent, a suffix
+                // keeps the literal an f32 without a cast read as generics by '<'
+                format!("{value:?}f32")
+            }
+        }
+        Variable::Int(value) => value.unwrap().to_string(),
+        Variable::String(value) => {
+            let value = value.unwrap();
+            if value.is_empty() {
+                "String::new()".to_owned()
+            } else {
+                format!("{:?}.to_owned()", value)
+            }
+        }
+        Variable::Bool(value) => value.unwrap().to_string(),
+        Variable::Array(..) => unreachable!("arrays are never folded"),
+    }
+}
+
 impl Transpile for Expression {
-  fn transpile(&mut self) -> std::io::Result<String> {
-      match self.expression_type {
-        ExpressionType::Variable => {
-          self.execute()?;
+    fn transpile(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        if let Some(value) = self.context.optimizations.folded(&self.folder) {
+            return Ok(transpile_value(value));
+        }
+
+        let token = self.transpile_operation(analysis)?;
 
-          let folder_count = subfolder_count(&self.folders[1])?;
-          Ok(format!("var_{folder_count}"))
+        // Rust booleans are 1 or 0 without --booleans
+        if self.expression_type.is_boolean() && analysis.type_of(&self.folder)? == Type::Int {
+            Ok(format!("(({token}) as i32)"))
+        } else {
+            Ok(token)
         }
+    }
+}
 
-        ExpressionType::LiteralValue => {
-          let value = match self.get_literal_value()? {
-            Variable::Char(value) => format!("'{}'", value.unwrap()),
-            Variable::Float(value) => format!("{} as f32", value.unwrap()),
-            Variable::Int(value) => value.unwrap().to_string(),
-            Variable::String(value) => {
-              let value = value.unwrap();
-              if value.is_empty() {
-                "String::new()".to_owned()
-              } else {
-                format!("\"{}\".to_owned()", value)
-              }
+impl Expression {
+    /// Rust code of this expression, comparisons and logical operations give a Rust bool
+    fn transpile_operation(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        match self.expression_type {
+            ExpressionType::Variable => Ok(format!("var_{}", self.variable_index()?)),
+
+            ExpressionType::LiteralValue => Ok(transpile_value(&self.get_literal_value()?)),
+
+            ExpressionType::Not => Ok(format!(
+                "!({})",
+                self.operand()?.transpile_condition(analysis)?
+            )),
+
+            ExpressionType::Call => Ok(format!(
+                "proc_{}({})",
+                self.procedure_index()?,
+                procedure::transpile_arguments(
+                    self.arguments_folder(),
+                    &self.scope,
+                    &self.context,
+                    analysis
+                )?
+            )),
+
+            ExpressionType::IndexGet => {
+                let operands = self.sequence_operands()?;
+                let sequence = operands[0].transpile(analysis)?;
+                let index = operands[1].transpile(analysis)?;
+
+                if analysis.type_of(operands[0].folder())? == Type::String {
+                    Ok(format!(
+                        "({sequence}).chars().nth(({index}) as usize).expect(\"index out of bounds\")"
+                    ))
+                } else {
+                    Ok(format!("({sequence})[({index}) as usize].clone()"))
+                }
+            }
+
+            ExpressionType::Substring => {
+                let operands = self.sequence_operands()?;
+
+                Ok(format!(
+                    "{{ let chars: Vec<char> = ({}).chars().collect(); \
+                    let (start, end) = ({}, {}); \
+                    if start < 0 || end < start || end as usize > chars.len() {{ \
+                    panic!(\"substring from {{}} to {{}} out of bounds\", start, end) }} \
+                    chars[start as usize..end as usize].iter().collect::<String>() }}",
+                    operands[0].transpile(analysis)?,
+                    operands[1].transpile(analysis)?,
+                    operands[2].transpile(analysis)?
+                ))
+            }
+
+            ExpressionType::Cast => self.transpile_cast(analysis),
+
+            ExpressionType::Length => {
+                let operand = self.operand()?;
+                let sequence = operand.transpile(analysis)?;
+
+                // strings are counted in chars, as they are indexed
+                if analysis.type_of(operand.folder())? == Type::String {
+                    Ok(format!("(({sequence}).chars().count() as i32)"))
+                } else {
+                    Ok(format!("(({sequence}).len() as i32)"))
+                }
+            }
+
+            ExpressionType::IndexSet | ExpressionType::Push => {
+                let operands = self.sequence_operands()?;
+                let array = operands[0].transpile(analysis)?;
+                let value = operands.last().unwrap().transpile_owned(analysis)?;
+
+                let update = match self.expression_type {
+                    ExpressionType::IndexSet => format!(
+                        "array[({}) as usize] = {value};",
+                        operands[1].transpile(analysis)?
+                    ),
+                    _ => format!("array.push({value});"),
+                };
+
+                // arrays are values, the operand is left untouched
+                Ok(format!(
+                    "{{ let mut array = ({array}).clone(); {update} array }}"
+                ))
             }
-          };
 
-          Ok(value)
+            ExpressionType::And | ExpressionType::Or => {
+                let (first, second) = self.operands()?;
+                let operator = match self.expression_type {
+                    ExpressionType::And => "&&",
+                    _ => "||",
+                };
+
+                Ok(format!(
+                    "({}) {operator} ({})",
+                    first.transpile_condition(analysis)?,
+                    second.transpile_condition(analysis)?
+                ))
+            }
+
+            _ => {
+                let (first, second) = self.operands()?;
+                let a = analysis.type_of(first.folder())?;
+                let b = analysis.type_of(second.folder())?;
+
+                let concat = self.expression_type == ExpressionType::Add
+                    && a == Type::String
+                    && b == Type::String;
+                let char_concat = self.expression_type == ExpressionType::Add
+                    && (a == Type::Char || b == Type::Char)
+                    && analysis.type_of(&self.folder)? == Type::String;
+
+                // Ints are promoted to f32 as soon as one of the operands is a Float
+                let numeric = |t: Type| t == Type::Int || t == Type::Float;
+                let float = numeric(a)
+                    && numeric(b)
+                    && (a == Type::Float
+                        || b == Type::Float
+                        || self.expression_type == ExpressionType::Divide);
+
+                let mut left = first.transpile(analysis)?;
+                if first.expression_type != ExpressionType::Variable
+                    && first.expression_type != ExpressionType::LiteralValue
+                {
+                    left = format!("({left})");
+                } else if concat && first.expression_type == ExpressionType::Variable {
+                    // the variable must still be usable after the concatenation
+                    left += ".clone()";
+                }
+                if float && a == Type::Int {
+                    left = format!("({left} as f32)");
+                }
+
+                let mut right = second.transpile(analysis)?;
+                if second.expression_type != ExpressionType::Variable
+                    && second.expression_type != ExpressionType::LiteralValue
+                {
+                    right = format!("({right})");
+                }
+                if float && b == Type::Int {
+                    right = format!("({right} as f32)");
+                }
+
+                let operators = HashMap::from([
+                    (ExpressionType::Add, "+"),
+                    (ExpressionType::Substract, "-"),
+                    (ExpressionType::Multiply, "*"),
+                    (ExpressionType::Divide, "/"),
+                    (ExpressionType::GreaterThan, ">"),
+                    (ExpressionType::LessThan, "<"),
+                    (ExpressionType::EqualTo, "=="),
+                    (ExpressionType::Modulo, "%"),
+                    (ExpressionType::NotEqualTo, "!="),
+                    (ExpressionType::BitAnd, "&"),
+                    (ExpressionType::BitOr, "|"),
+                    (ExpressionType::BitXor, "^"),
+                ]);
+
+                if concat {
+                    Ok(format!("{left} + &{right}"))
+                } else if char_concat {
+                    Ok(format!("format!(\"{{}}{{}}\", {left}, {right})"))
+                } else if self.expression_type == ExpressionType::ShiftLeft {
+                    Ok(format!("i32::wrapping_shl({left}, {right} as u32)"))
+                } else if self.expression_type == ExpressionType::ShiftRight {
+                    Ok(format!("i32::wrapping_shr({left}, {right} as u32)"))
+                } else if a == Type::Int
+                    && b == Type::Int
+                    && NumericMode::applies_to(self.expression_type)
+                {
+                    Ok(self
+                        .context
+                        .numeric
+                        .transpile(self.expression_type, &left, &right))
+                } else if a == Type::Int
+                    && b == Type::Int
+                    && self.expression_type == ExpressionType::Modulo
+                {
+                    Ok(format!("i32::wrapping_rem({left}, {right})"))
+                } else if float
+                    && self.context.numeric == NumericMode::Checked
+                    && matches!(
+                        self.expression_type,
+                        ExpressionType::Divide | ExpressionType::Modulo
+                    )
+                {
+                    Ok(format!(
+                        "{{ let (a, b): (f32, f32) = ({left}, {right}); \
+                        if b == 0.0 {{ panic!(\"division by zero\") }} a {} b }}",
+                        operators.get(&self.expression_type).unwrap()
+                    ))
+                } else {
+                    Ok(format!(
+                        "{left} {} {right}",
+                        operators.get(&self.expression_type).unwrap()
+                    ))
+                }
+            }
         }
+    }
+}
 
-        _ => {
-          let left: String;
-          {
-            let mut exp = Self::new(&self.folders[1], &self.scope)?;
-            if exp.expression_type != ExpressionType::Variable && exp.expression_type != ExpressionType::LiteralValue {
-              left = format!("({})", exp.transpile()?);
-            } else {
-              left = exp.transpile()?;
+impl Expression {
+    /// Rust conversion of a 'cast' expression, conversions failing at runtime panic
+    fn transpile_cast(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        let operand = self.cast_operand()?;
+        let value = operand.transpile_owned(analysis)?;
+        let from = analysis.type_of(operand.folder())?;
+        let to = self.target_type()?;
+
+        let token = match (from, to) {
+            (from, to) if from == to => value,
+            (Type::Int, Type::Float) => format!("(({value}) as f32)"),
+            (Type::Int, Type::Char) => {
+                format!("char::from_u32(({value}) as u32).expect(\"invalid char code point\")")
             }
-          }
-          
-          let right: String;
-          let mut right_is_string = false;
-          {
-            let mut exp = Self::new(&self.folders[2], &self.scope)?;
-            if exp.expression_type != ExpressionType::Variable && exp.expression_type != ExpressionType::LiteralValue {
-              right = format!("({})", exp.transpile()?);
-            } else {
-              right = exp.transpile()?;
-              
-              let value_type = exp.execute()?.get_type();
-              right_is_string = value_type == Type::String; 
+            (Type::Float, Type::Int) => format!(
+                "{{ let value: f32 = {value}; \
+                if !(value >= i32::MIN as f32 && value < -(i32::MIN as f32)) {{ \
+                panic!(\"can not cast Float {{}} to Int\", value) }} value as i32 }}"
+            ),
+            (Type::Int, Type::Bool) => format!("(({value}) != 0)"),
+            (Type::Float, Type::Bool) => format!("(({value}) != 0.0)"),
+            (Type::Char, Type::Bool) => format!("(({value}) != '\\0')"),
+            (Type::Char | Type::Bool, Type::Int) => format!("(({value}) as i32)"),
+            (Type::String, Type::Char) => format!(
+                "{{ let value: String = {value}; let mut chars = value.chars(); \
+                match (chars.next(), chars.next()) {{ (Some(value), None) => value, \
+                _ => panic!(\"can not cast String {{}} to Char\", value) }} }}"
+            ),
+            (Type::String, to) => format!(
+                "({value}).trim().parse::<{}>().expect(\"can not cast String to {to:?}\")",
+                to.as_str()
+            ),
+            (_, Type::String) => format!("({value}).to_string()"),
+            _ => {
+                return Err(input_error(format!(
+                    "{} : can not cast {from:?} to {to:?}",
+                    self.folder
+                )))
+            }
+        };
+
+        Ok(token)
+    }
+
+    /// Rust value of this expression that can be moved, variables holding a String or an Array are cloned
+    pub fn transpile_owned(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        let token = self.transpile(analysis)?;
+
+        match analysis.type_of(&self.folder)? {
+            Type::String | Type::Array(_) if self.expression_type == ExpressionType::Variable => {
+                Ok(token + ".clone()")
             }
-          }
-
-          let operators = HashMap::from([
-            (ExpressionType::Add, "+"),
-            (ExpressionType::Substract, "-"),
-            (ExpressionType::Multiply, "*"),
-            (ExpressionType::Divide, "/"),
-            (ExpressionType::GreaterThan, ">"),
-            (ExpressionType::LessThan, "<"),
-            (ExpressionType::EqualTo, "=="),
-          ]);
-
-          if right_is_string && self.expression_type == ExpressionType::Add {
-            Ok(format!("{left} + &{right}"))
-          } else {
-            Ok(format!("{left} {} {right}", operators.get(&self.expression_type).unwrap()))
-          }
+            _ => Ok(token),
         }
-      }
-  }
-}
\ No newline at end of file
+    }
+
+    /// Truthiness of this expression as a Rust boolean
+    pub fn transpile_condition(&self, analysis: &ScopeAnalysis) -> std::io::Result<String> {
+        // folded comparisons are plain literals
+        if self.expression_type.is_boolean()
+            && self.context.optimizations.folded(&self.folder).is_none()
+        {
+            return self.transpile_operation(analysis);
+        }
+
+        let token = self.transpile(analysis)?;
+
+        let token = match analysis.type_of(&self.folder)? {
+            Type::Char => format!("{token} != '\\0'"),
+            Type::Float => format!("{token} != 0.0"),
+            Type::Int => format!("{token} != 0"),
+            Type::String => {
+                if self.expression_type == ExpressionType::Add {
+                    format!("!({token}).is_empty()")
+                } else {
+                    format!("!{token}.is_empty()")
+                }
+            }
+            Type::Bool => token,
+            Type::Array(_) => format!("!({token}).is_empty()"),
+        };
+
+        Ok(token)
+    }
+}
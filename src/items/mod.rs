@@ -1,3 +1,4 @@
 pub mod command;
 pub mod expression;
+pub mod procedure;
 pub mod variable;
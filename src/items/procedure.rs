@@ -0,0 +1,105 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    analysis::ScopeAnalysis, context::Context, interpreter::Interpreter, scope::Scope,
+    utils::input_error,
+};
+
+use super::{expression::Expression, variable::Variable};
+
+/// Procedure registered by a 'define' command
+#[derive(Debug, Clone)]
+pub struct Procedure {
+    /// folder of the 'define' command
+    pub folder: String,
+
+    /// folder holding the list of commands of the procedure
+    pub body: String,
+
+    pub parameters: usize,
+}
+
+/// Run procedure `proc_{index}` with the values of the expressions in `arguments_folder`.
+///
+/// Returns the value of the 'return' command that ended the call, if any.
+pub fn call(
+    folder: &str,
+    index: usize,
+    arguments_folder: &str,
+    scope: &Rc<RefCell<Scope>>,
+    context: &Rc<Context>,
+) -> std::io::Result<Option<Variable>> {
+    let (body, procedure_scope) = enter(folder, index, arguments_folder, scope, context)?;
+
+    let result = Interpreter::new(&body, procedure_scope, context).and_then(|mut body| body.run());
+    let value = context.calls.borrow_mut().pop().flatten();
+    result?;
+
+    Ok(value)
+}
+
+/// Start a call to `proc_{index}`, the call must be popped from `context.calls` once done.
+///
+/// Arguments are bound to var_0, var_1, ... in a new scope whose parent is the
+/// global scope. Returns the folder of the procedure body and its scope.
+pub fn enter(
+    folder: &str,
+    index: usize,
+    arguments_folder: &str,
+    scope: &Rc<RefCell<Scope>>,
+    context: &Rc<Context>,
+) -> std::io::Result<(String, Rc<RefCell<Scope>>)> {
+    let procedure = context
+        .procedures
+        .borrow()
+        .get(&index)
+        .cloned()
+        .ok_or_else(|| input_error(format!("{folder} : procedure proc_{index} is not defined")))?;
+
+    let arguments = context.cache.sorted_subfolders(arguments_folder)?;
+    if arguments.len() != procedure.parameters {
+        return Err(input_error(format!(
+            "{folder} : proc_{index} expects {} arguments, {} found",
+            procedure.parameters,
+            arguments.len()
+        )));
+    }
+
+    if context.calls.borrow().len() >= context.max_call_depth {
+        return Err(input_error(format!(
+            "{folder} : maximum call depth of {} reached",
+            context.max_call_depth
+        )));
+    }
+
+    let procedure_scope = Rc::new(RefCell::new(Scope::new(Some(Scope::global(scope)))));
+    for (i, argument) in arguments.iter().enumerate() {
+        let value = Expression::new(argument, scope, context)?.execute()?;
+
+        let mut procedure_scope = procedure_scope.borrow_mut();
+        procedure_scope.declare_variable_with_type(value.get_type(), i)?;
+        procedure_scope.set_variable(i, value)?;
+    }
+
+    context.calls.borrow_mut().push(None);
+
+    Ok((procedure.body, procedure_scope))
+}
+
+/// Argument list of a call as Rust code
+pub fn transpile_arguments(
+    arguments_folder: &str,
+    scope: &Rc<RefCell<Scope>>,
+    context: &Rc<Context>,
+    analysis: &ScopeAnalysis,
+) -> std::io::Result<String> {
+    let mut arguments = vec![];
+
+    for argument in context.cache.sorted_subfolders(arguments_folder)? {
+        // strings and arrays are passed by value
+        let exp = Expression::new(&argument, scope, context)?;
+        arguments.push(exp.transpile_owned(analysis)?);
+    }
+
+    Ok(arguments.join(", "))
+}
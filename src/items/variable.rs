@@ -1,4 +1,9 @@
-use std::ops::{Add, Div, Mul, Sub};
+use std::cmp::Ordering;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub};
+
+use crate::{
+    context::Context, items::expression::ExpressionType, numeric::NumericMode, utils::input_error,
+};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Variable {
@@ -6,15 +11,26 @@ pub enum Variable {
     Float(Option<f32>),
     String(Option<std::string::String>),
     Char(Option<char>),
+
+    /// extension : result of comparisons and logical operations with --booleans
+    Bool(Option<bool>),
+
+    /// extension : homogeneous list, holding its element type
+    Array(Type, Option<Vec<Variable>>),
 }
 
-#[repr(C)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 pub enum Type {
     Int,
     Float,
     String,
     Char,
+
+    /// extension
+    Bool,
+
+    /// extension : elements can not be arrays themselves
+    Array(&'static Type),
 }
 
 impl Type {
@@ -24,6 +40,70 @@ impl Type {
             Self::Float => "f32",
             Self::Int => "i32",
             Self::String => "String",
+            Self::Bool => "bool",
+            Self::Array(Self::Bool) => "Vec<bool>",
+            Self::Array(Self::Char) => "Vec<char>",
+            Self::Array(Self::Float) => "Vec<f32>",
+            Self::Array(Self::Int) => "Vec<i32>",
+            Self::Array(_) => "Vec<String>",
+        }
+    }
+
+    /// Type of the arrays holding `element`, None if `element` is an array
+    pub fn array_of(element: Type) -> Option<Type> {
+        match element {
+            Self::Int => Some(Self::Array(&Self::Int)),
+            Self::Float => Some(Self::Array(&Self::Float)),
+            Self::String => Some(Self::Array(&Self::String)),
+            Self::Char => Some(Self::Array(&Self::Char)),
+            Self::Bool => Some(Self::Array(&Self::Bool)),
+            Self::Array(_) => None,
+        }
+    }
+
+    /// Type described by a type folder, the first subfolder of an array type holds its element type
+    pub fn read(folder: &str, context: &Context) -> std::io::Result<Type> {
+        let subfolders = context.cache.sorted_subfolders(folder)?;
+        if subfolders.len() != 4 {
+            let var_type = Type::try_from(subfolders.len())
+                .map_err(|err| input_error(format!("{folder} : {err}")))?;
+
+            if var_type == Type::Bool && !context.extensions {
+                return Err(input_error(format!(
+                    "{folder} : Bool type is an extension, run with --extensions"
+                )));
+            }
+
+            return Ok(var_type);
+        }
+
+        if !context.extensions {
+            return Err(input_error(format!(
+                "{folder} : Array type is an extension, run with --extensions"
+            )));
+        }
+
+        let element = context.cache.subfolder_count(&subfolders[0])?;
+        Type::try_from(element)
+            .ok()
+            .and_then(Type::array_of)
+            .ok_or_else(|| {
+                input_error(format!(
+                    "{} : invalid array element type, found {element} folders",
+                    subfolders[0]
+                ))
+            })
+    }
+
+    /// Number of folders identifying this type, arrays are identified by 4
+    pub fn folder_count(&self) -> usize {
+        match self {
+            Self::Int => 0,
+            Self::Float => 1,
+            Self::String => 2,
+            Self::Char => 3,
+            Self::Array(_) => 4,
+            Self::Bool => 5,
         }
     }
 }
@@ -67,40 +147,38 @@ impl std::fmt::Display for Variable {
                 f,
                 "{}",
                 if value.is_some() {
-                    format!("{}", value.clone().unwrap())
+                    value.clone().unwrap().to_string()
                 } else {
                     "null".to_owned()
                 }
             ),
+
+            Self::Bool(value) => match value {
+                Some(value) => write!(f, "{value}"),
+                None => write!(f, "null"),
+            },
+
+            Self::Array(_, values) => match values {
+                Some(values) => {
+                    let values: Vec<String> =
+                        values.iter().map(|value| value.to_string()).collect();
+                    write!(f, "[{}]", values.join(", "))
+                }
+                None => write!(f, "null"),
+            },
         }
     }
 }
 
-#[allow(dead_code)]
 impl Variable {
-    /// Create a new variable
-    ///
-    /// Parameter
-    /// - folders_count : used to indentify variable type
-    pub fn new(folders_count: usize) -> std::io::Result<Self> {
-        match folders_count {
-            0 => Ok(Variable::Int(None)),
-            1 => Ok(Variable::Float(None)),
-            2 => Ok(Variable::String(None)),
-            3 => Ok(Variable::Char(None)),
-            count => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid variable type : {count} folders found."),
-            )),
-        }
-    }
-
     pub fn get_type(&self) -> Type {
         match self {
             Variable::Int(_) => Type::Int,
             Variable::Float(_) => Type::Float,
             Variable::String(_) => Type::String,
             Variable::Char(_) => Type::Char,
+            Variable::Bool(_) => Type::Bool,
+            Variable::Array(element, _) => Type::array_of(*element).unwrap(),
         }
     }
 
@@ -110,6 +188,8 @@ impl Variable {
             Self::Float(value) => value.is_none(),
             Self::Int(value) => value.is_none(),
             Self::String(value) => value.is_none(),
+            Self::Bool(value) => value.is_none(),
+            Self::Array(_, values) => values.is_none(),
         }
     }
 
@@ -147,12 +227,108 @@ impl Variable {
                     false
                 }
             }
+            Self::Bool(value) => value.unwrap_or(false),
+            Self::Array(_, values) => {
+                if let Some(values) = values {
+                    !values.is_empty()
+                } else {
+                    false
+                }
+            }
         }
     }
 
     pub fn is_falsy(&self) -> bool {
         !self.is_truthy()
     }
+
+    /// Value converted to `to`, None if the conversion is not supported or fails.
+    /// Floats are truncated, Strings are parsed once trimmed, Ints are Char code points
+    pub fn cast(&self, to: Type) -> Option<Variable> {
+        let value = match (self, to) {
+            (value, to) if value.get_type() == to => value.clone(),
+
+            (Self::Int(Some(value)), Type::Float) => Self::Float(Some(*value as f32)),
+            (Self::Int(Some(value)), Type::Char) => {
+                Self::Char(Some(char::from_u32(*value as u32)?))
+            }
+            (Self::Int(Some(value)), Type::Bool) => Self::Bool(Some(*value != 0)),
+
+            // NaN, infinities and values out of the Int range can not be truncated
+            (Self::Float(Some(value)), Type::Int)
+                if *value >= i32::MIN as f32 && *value < -(i32::MIN as f32) =>
+            {
+                Self::Int(Some(*value as i32))
+            }
+            (Self::Float(Some(value)), Type::Bool) => Self::Bool(Some(*value != 0.0)),
+
+            (Self::String(Some(value)), Type::Int) => Self::Int(Some(value.trim().parse().ok()?)),
+            (Self::String(Some(value)), Type::Float) => {
+                Self::Float(Some(value.trim().parse().ok()?))
+            }
+            (Self::String(Some(value)), Type::Bool) => Self::Bool(Some(value.trim().parse().ok()?)),
+            (Self::String(Some(value)), Type::Char) => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(value), None) => Self::Char(Some(value)),
+                    _ => return None,
+                }
+            }
+
+            (Self::Char(Some(value)), Type::Int) => Self::Int(Some(*value as i32)),
+            (Self::Char(Some(value)), Type::Bool) => Self::Bool(Some(*value != '\0')),
+
+            (Self::Bool(Some(value)), Type::Int) => Self::Int(Some(*value as i32)),
+
+            (
+                Self::Int(Some(_))
+                | Self::Float(Some(_))
+                | Self::Char(Some(_))
+                | Self::Bool(Some(_)),
+                Type::String,
+            ) => Self::String(Some(self.to_string())),
+
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Order of two values, Ints are promoted when compared to Floats.
+    /// None if the values can not be compared or one of them is NaN
+    pub fn compare(&self, other: &Variable) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Int(Some(a)), Self::Int(Some(b))) => a.partial_cmp(b),
+            (Self::Int(Some(a)), Self::Float(Some(b))) => (*a as f32).partial_cmp(b),
+            (Self::Float(Some(a)), Self::Int(Some(b))) => a.partial_cmp(&(*b as f32)),
+            (Self::Float(Some(a)), Self::Float(Some(b))) => a.partial_cmp(b),
+            (Self::String(Some(a)), Self::String(Some(b))) => a.partial_cmp(b),
+            (Self::Char(Some(a)), Self::Char(Some(b))) => a.partial_cmp(b),
+            (Self::Bool(Some(a)), Self::Bool(Some(b))) => a.partial_cmp(b),
+
+            // lexicographic order, as Rust vectors
+            (Self::Array(_, Some(a)), Self::Array(_, Some(b))) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.compare(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+                a.len().partial_cmp(&b.len())
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Operand of a bitwise operation
+    fn bits(&self) -> i32 {
+        match self {
+            Self::Int(Some(value)) => *value,
+            Self::Int(None) => panic!("Use of uninitialized int variable"),
+            _ => panic!("Bitwise operations only apply to Int"),
+        }
+    }
 }
 
 impl From<Type> for Variable {
@@ -162,21 +338,43 @@ impl From<Type> for Variable {
             Type::Int => Variable::Int(None),
             Type::Float => Variable::Float(None),
             Type::String => Variable::String(None),
+            Type::Bool => Variable::Bool(None),
+            Type::Array(element) => Variable::Array(*element, None),
         }
     }
 }
 
-impl From<usize> for Type {
-    fn from(value: usize) -> Self {
+/// Types identified by their folder count alone, arrays also need their element type
+impl TryFrom<usize> for Type {
+    type Error = std::io::Error;
+
+    fn try_from(value: usize) -> std::io::Result<Self> {
         match value {
-            1 => Self::Float,
-            2 => Self::String,
-            3 => Self::Char,
-            _ => Self::Int,
+            0 => Ok(Self::Int),
+            1 => Ok(Self::Float),
+            2 => Ok(Self::String),
+            3 => Ok(Self::Char),
+            5 => Ok(Self::Bool),
+            4 => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Array type is only allowed in declarations".to_owned(),
+            )),
+            count => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid type : {count} folders found."),
+            )),
         }
     }
 }
 
+/// Int arithmetic of the operators, in the default numeric mode. Expressions
+/// apply the mode of the run before reaching them
+fn int_operation(operator: ExpressionType, a: i32, b: i32) -> i32 {
+    NumericMode::default()
+        .apply(operator, a, b)
+        .expect("wrapping operations never overflow")
+}
+
 impl Div<Variable> for Variable {
     type Output = Variable;
 
@@ -190,6 +388,14 @@ impl Div<Variable> for Variable {
                 panic!("Can not divide a String");
             }
 
+            Self::Array(..) => {
+                panic!("Can not divide an Array");
+            }
+
+            Self::Bool(_) => {
+                panic!("Can not divide a Bool");
+            }
+
             Self::Float(value) => {
                 if value.is_none() {
                     panic!("Use of uninitialized float variable");
@@ -197,8 +403,8 @@ impl Div<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not divide Float by Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not divide Float by Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -206,7 +412,7 @@ impl Div<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value / other_value));
+                        Variable::Float(Some(value / other_value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -214,7 +420,7 @@ impl Div<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value / other_value as f32));
+                        Variable::Float(Some(value / other_value as f32))
                     }
                 }
             }
@@ -226,8 +432,8 @@ impl Div<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not divide Int by Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not divide Int by Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -235,7 +441,7 @@ impl Div<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value as f32 / other_value));
+                        Variable::Float(Some(value as f32 / other_value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -243,7 +449,7 @@ impl Div<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value as f32 / other_value as f32));
+                        Variable::Float(Some(value as f32 / other_value as f32))
                     }
                 }
             }
@@ -264,6 +470,14 @@ impl Mul<Variable> for Variable {
                 panic!("Can not multiply a String");
             }
 
+            Self::Array(..) => {
+                panic!("Can not multiply an Array");
+            }
+
+            Self::Bool(_) => {
+                panic!("Can not multiply a Bool");
+            }
+
             Self::Float(value) => {
                 if value.is_none() {
                     panic!("Use of uninitialized float variable");
@@ -271,8 +485,8 @@ impl Mul<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not multiply Float by Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not multiply Float by Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -280,7 +494,7 @@ impl Mul<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value * value));
+                        Variable::Float(Some(other_value * value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -288,7 +502,7 @@ impl Mul<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value as f32 * value));
+                        Variable::Float(Some(other_value as f32 * value))
                     }
                 }
             }
@@ -300,8 +514,8 @@ impl Mul<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not multiply Int by Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not multiply Int by Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -309,7 +523,7 @@ impl Mul<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value * value as f32));
+                        Variable::Float(Some(other_value * value as f32))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -317,7 +531,11 @@ impl Mul<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Int(Some(other_value * value));
+                        Variable::Int(Some(int_operation(
+                            ExpressionType::Multiply,
+                            value,
+                            other_value,
+                        )))
                     }
                 }
             }
@@ -338,6 +556,14 @@ impl Sub<Variable> for Variable {
                 panic!("Can not substract from a String");
             }
 
+            Self::Array(..) => {
+                panic!("Can not substract from an Array");
+            }
+
+            Self::Bool(_) => {
+                panic!("Can not substract from a Bool");
+            }
+
             Self::Float(value) => {
                 if value.is_none() {
                     panic!("Use of uninitialized float variable");
@@ -345,8 +571,8 @@ impl Sub<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not substract Float to Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not substract Float to Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -354,7 +580,7 @@ impl Sub<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value - other_value));
+                        Variable::Float(Some(value - other_value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -362,7 +588,7 @@ impl Sub<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value - other_value as f32));
+                        Variable::Float(Some(value - other_value as f32))
                     }
                 }
             }
@@ -374,8 +600,8 @@ impl Sub<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not substract Int to Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not substract Int to Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -383,7 +609,7 @@ impl Sub<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(value as f32 - other_value));
+                        Variable::Float(Some(value as f32 - other_value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -391,7 +617,11 @@ impl Sub<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Int(Some(value - other_value));
+                        Variable::Int(Some(int_operation(
+                            ExpressionType::Substract,
+                            value,
+                            other_value,
+                        )))
                     }
                 }
             }
@@ -404,28 +634,31 @@ impl Add<Variable> for Variable {
 
     fn add(self, other: Variable) -> Variable {
         match self {
-            Self::Char(_) => {
-                panic!("Can not add to a Char");
+            Self::Array(..) => {
+                panic!("Can not add to an Array");
             }
 
-            Self::String(value) => {
-                if value.is_none() {
-                    panic!("Use of uninitialized float variable");
-                }
-                let value = value.unwrap();
-
-                if let Self::String(other_value) = other {
-                    if other_value.is_none() {
-                        panic!("Use of uninitialized string variable");
-                    }
-                    let other_value = other_value.unwrap();
+            Self::Bool(_) => {
+                panic!("Can not add to a Bool");
+            }
 
-                    return Variable::String(Some(format!("{value}{other_value}")));
-                } else {
-                    let other_type = other.get_type();
-                    panic!("Can not add String to {:?}", other_type);
+            // Chars are concatenated to Strings and to other Chars
+            Self::Char(_) | Self::String(_) => match (&self, &other) {
+                (Self::Char(None) | Self::String(None), _)
+                | (_, Self::Char(None) | Self::String(None)) => {
+                    panic!("Use of uninitialized string variable");
                 }
-            }
+                (_, Self::Char(_) | Self::String(_)) => {
+                    Variable::String(Some(format!("{self}{other}")))
+                }
+                _ => {
+                    panic!(
+                        "Can not add {:?} to {:?}",
+                        self.get_type(),
+                        other.get_type()
+                    );
+                }
+            },
 
             Self::Float(value) => {
                 if value.is_none() {
@@ -434,8 +667,8 @@ impl Add<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not add Float to Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not add Float to Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -443,7 +676,7 @@ impl Add<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value + value));
+                        Variable::Float(Some(other_value + value))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -451,7 +684,7 @@ impl Add<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value as f32 + value));
+                        Variable::Float(Some(other_value as f32 + value))
                     }
                 }
             }
@@ -463,8 +696,8 @@ impl Add<Variable> for Variable {
                 let value = value.unwrap();
 
                 match other {
-                    Self::Char(_) | Self::String(_) => {
-                        panic!("Can not add Int to Char or String");
+                    Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..) => {
+                        panic!("Can not add Int to Char, String, Bool or Array");
                     }
                     Self::Float(other_value) => {
                         if other_value.is_none() {
@@ -472,7 +705,7 @@ impl Add<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Float(Some(other_value + value as f32));
+                        Variable::Float(Some(other_value + value as f32))
                     }
                     Self::Int(other_value) => {
                         if other_value.is_none() {
@@ -480,10 +713,117 @@ impl Add<Variable> for Variable {
                         }
                         let other_value = other_value.unwrap();
 
-                        return Variable::Int(Some(other_value + value));
+                        Variable::Int(Some(int_operation(ExpressionType::Add, value, other_value)))
                     }
                 }
             }
         }
     }
 }
+
+impl Rem<Variable> for Variable {
+    type Output = Variable;
+
+    fn rem(self, other: Variable) -> Self::Output {
+        match (self, other) {
+            (Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..), _)
+            | (_, Self::Char(_) | Self::String(_) | Self::Bool(_) | Self::Array(..)) => {
+                panic!("Can not apply modulo to Char, String, Bool or Array");
+            }
+
+            (Self::Int(Some(_)), Self::Int(Some(0))) => {
+                panic!("Modulo by zero");
+            }
+
+            (Self::Int(Some(value)), Self::Int(Some(other_value))) => {
+                Variable::Int(Some(value.wrapping_rem(other_value)))
+            }
+
+            (Self::Int(Some(value)), Self::Float(Some(other_value))) => {
+                Variable::Float(Some(value as f32 % other_value))
+            }
+
+            (Self::Float(Some(value)), Self::Int(Some(other_value))) => {
+                Variable::Float(Some(value % other_value as f32))
+            }
+
+            (Self::Float(Some(value)), Self::Float(Some(other_value))) => {
+                Variable::Float(Some(value % other_value))
+            }
+
+            _ => panic!("Use of uninitialized variable"),
+        }
+    }
+}
+
+impl BitAnd<Variable> for Variable {
+    type Output = Variable;
+
+    fn bitand(self, other: Variable) -> Self::Output {
+        Variable::Int(Some(self.bits() & other.bits()))
+    }
+}
+
+impl BitOr<Variable> for Variable {
+    type Output = Variable;
+
+    fn bitor(self, other: Variable) -> Self::Output {
+        Variable::Int(Some(self.bits() | other.bits()))
+    }
+}
+
+impl BitXor<Variable> for Variable {
+    type Output = Variable;
+
+    fn bitxor(self, other: Variable) -> Self::Output {
+        Variable::Int(Some(self.bits() ^ other.bits()))
+    }
+}
+
+/// Only the 5 lowest bits of the shift amount are used, as in most languages
+impl Shl<Variable> for Variable {
+    type Output = Variable;
+
+    fn shl(self, other: Variable) -> Self::Output {
+        Variable::Int(Some(self.bits().wrapping_shl(other.bits() as u32)))
+    }
+}
+
+/// Arithmetic shift, the sign is kept
+impl Shr<Variable> for Variable {
+    type Output = Variable;
+
+    fn shr(self, other: Variable) -> Self::Output {
+        Variable::Int(Some(self.bits().wrapping_shr(other.bits() as u32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(value: i32) -> Variable {
+        Variable::Int(Some(value))
+    }
+
+    #[test]
+    fn int_operators_wrap() {
+        assert_eq!(int(i32::MAX) + int(1), int(i32::MIN));
+        assert_eq!(int(i32::MIN) - int(1), int(i32::MAX));
+        assert_eq!(int(i32::MAX) * int(2), int(-2));
+        assert_eq!(int(i32::MIN) * int(-1), int(i32::MIN));
+        assert_eq!(int(i32::MIN) % int(-1), int(0));
+    }
+
+    #[test]
+    fn int_division_gives_floats() {
+        assert_eq!(int(i32::MIN) / int(-1), Variable::Float(Some(2147483648.0)));
+        assert_eq!(int(1) / int(0), Variable::Float(Some(f32::INFINITY)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Modulo by zero")]
+    fn int_modulo_by_zero_panics() {
+        let _ = int(i32::MAX) % int(0);
+    }
+}
@@ -0,0 +1,321 @@
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, fs, path::Path, rc::Rc};
+
+use crate::{
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+    },
+    scope::Scope,
+    utils::{is_directory, sorted_subfolders, subfolder_count},
+};
+
+pub struct Warning {
+    pub folder: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} : {}", self.folder, self.message)
+    }
+}
+
+/// Variable introduced by a 'declare', 'let' or 'input' command
+struct Declaration {
+    folder: String,
+    used: bool,
+}
+
+/// Look for layouts the interpreter accepts but that are easy to break
+pub fn lint(folder: &str, context: &Rc<Context>) -> std::io::Result<Vec<Warning>> {
+    let mut linter = Linter {
+        warnings: vec![],
+        scopes: vec![],
+        scope: Rc::new(RefCell::new(Scope::new(None))),
+        context: context.clone(),
+    };
+    linter.block(folder)?;
+
+    Ok(linter.warnings)
+}
+
+struct Linter {
+    warnings: Vec<Warning>,
+
+    /// declared variables, innermost scope last
+    scopes: Vec<HashMap<usize, Declaration>>,
+
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl Linter {
+    fn warn(&mut self, folder: &str, message: String) {
+        self.warnings.push(Warning {
+            folder: folder.to_owned(),
+            message,
+        });
+    }
+
+    /// Folder holding a list of commands, run in its own scope
+    fn block(&mut self, folder: &str) -> std::io::Result<()> {
+        self.scopes.push(HashMap::new());
+        self.ordered_folder(folder)?;
+
+        for subfolder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&subfolder, &self.scope, &self.context)?;
+            self.command(&cmd)?;
+        }
+
+        let mut unused: Vec<(usize, Declaration)> = self
+            .scopes
+            .pop()
+            .unwrap()
+            .into_iter()
+            .filter(|(_, declaration)| !declaration.used)
+            .collect();
+        unused.sort_by_key(|(index, _)| *index);
+
+        for (index, declaration) in unused {
+            self.warn(
+                &declaration.folder,
+                format!("var_{index} is declared but never read"),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn command(&mut self, cmd: &Command) -> std::io::Result<()> {
+        self.stray_files(cmd.folder())?;
+        self.ordered_folder(cmd.folder())?;
+
+        match cmd.command_type {
+            CommandType::Declare => {
+                let index = cmd.variable_index()?;
+                self.declare(cmd, index);
+            }
+
+            CommandType::Let | CommandType::Input => {
+                if cmd.command_type == CommandType::Let {
+                    self.expression(&cmd.expression()?)?;
+                }
+
+                let index = cmd.variable_index()?;
+                let visible = self.scopes.iter().any(|scope| scope.contains_key(&index));
+                if !visible {
+                    self.declare(cmd, index);
+                }
+            }
+
+            CommandType::Define => {
+                // procedures only see the global scope
+                let callers = self.scopes.split_off(1);
+                self.block(cmd.body_folder()?)?;
+                self.scopes.extend(callers);
+            }
+
+            CommandType::Call => {
+                self.arguments(cmd.arguments_folder()?)?;
+            }
+
+            CommandType::Print
+            | CommandType::CreateFolder
+            | CommandType::RemoveFolder
+            | CommandType::Return => {
+                self.expression(&cmd.expression()?)?;
+            }
+
+            CommandType::Write => {
+                self.expression(&cmd.expression()?)?;
+                self.expression(&cmd.value_expression()?)?;
+            }
+
+            CommandType::If | CommandType::While => {
+                self.expression(&cmd.expression()?)?;
+                self.block(cmd.body_folder()?)?;
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    self.block(else_folder)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn arguments(&mut self, folder: &str) -> std::io::Result<()> {
+        self.ordered_folder(folder)?;
+
+        for argument in sorted_subfolders(folder)? {
+            self.expression(&Expression::new(&argument, &self.scope, &self.context)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn declare(&mut self, cmd: &Command, index: usize) {
+        self.scopes.last_mut().unwrap().insert(
+            index,
+            Declaration {
+                folder: cmd.folder().to_owned(),
+                used: false,
+            },
+        );
+    }
+
+    fn expression(&mut self, exp: &Expression) -> std::io::Result<()> {
+        self.stray_files(exp.folder())?;
+        self.ordered_folder(exp.folder())?;
+
+        match exp.expression_type {
+            ExpressionType::Variable => {
+                let index = exp.variable_index()?;
+                let declaration = self
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find_map(|scope| scope.get_mut(&index));
+
+                if let Some(declaration) = declaration {
+                    declaration.used = true;
+                }
+            }
+
+            ExpressionType::LiteralValue => {
+                self.literal(&exp.subfolders()[2])?;
+            }
+
+            ExpressionType::Not => {
+                self.expression(&exp.operand()?)?;
+            }
+
+            ExpressionType::Cast => {
+                self.expression(&exp.cast_operand()?)?;
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                for operand in exp.sequence_operands()? {
+                    self.expression(&operand)?;
+                }
+            }
+
+            ExpressionType::Call => {
+                self.arguments(exp.arguments_folder())?;
+            }
+
+            _ => {
+                let (first, second) = exp.operands()?;
+                self.expression(&first)?;
+                self.expression(&second)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bytes of a literal value, made of two hex digits of four bits each
+    fn literal(&mut self, folder: &str) -> std::io::Result<()> {
+        self.ordered_folder(folder)?;
+
+        for byte in sorted_subfolders(folder)? {
+            self.ordered_folder(&byte)?;
+
+            for digit in sorted_subfolders(&byte)? {
+                self.ordered_folder(&digit)?;
+
+                for bit in sorted_subfolders(&digit)? {
+                    self.bit(&bit)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A bit is set as soon as its folder is not empty, only one empty subfolder is expected
+    fn bit(&mut self, folder: &str) -> std::io::Result<()> {
+        let subfolders = sorted_subfolders(folder)?;
+
+        if subfolders.len() > 1 {
+            self.warn(
+                folder,
+                format!(
+                    "bit folder holds {} subfolders, it is read as 1",
+                    subfolders.len()
+                ),
+            );
+        } else if let Some(subfolder) = subfolders.first() {
+            if subfolder_count(subfolder)? != 0 {
+                self.warn(
+                    folder,
+                    "bit folder holds nested subfolders, it is read as 1".to_owned(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Files are skipped by the interpreter
+    fn stray_files(&mut self, folder: &str) -> std::io::Result<()> {
+        let mut files: Vec<String> = fs::read_dir(folder)?
+            .flatten()
+            .filter(|entry| !is_directory(entry))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+
+        for file in files {
+            self.warn(folder, format!("'{file}' is not a folder and is ignored"));
+        }
+
+        Ok(())
+    }
+
+    /// Folder whose subfolders are read in order
+    fn ordered_folder(&mut self, folder: &str) -> std::io::Result<()> {
+        let names: Vec<String> = sorted_subfolders(folder)?
+            .iter()
+            .map(|subfolder| {
+                Path::new(subfolder)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+
+        for pair in names.windows(2) {
+            let ordering = human_sort::compare(&pair[0].to_lowercase(), &pair[1].to_lowercase());
+            if ordering == Ordering::Equal {
+                self.warn(
+                    folder,
+                    format!(
+                        "'{}' and '{}' only differ by case, their order depends on the file system",
+                        pair[0], pair[1]
+                    ),
+                );
+            }
+        }
+
+        // file managers usually list folders in plain alphabetical order
+        let mut alphabetical = names.clone();
+        alphabetical.sort();
+
+        if let Some(i) = (0..names.len()).find(|i| names[*i] != alphabetical[*i]) {
+            self.warn(
+                folder,
+                format!(
+                    "'{}' is read before '{}' although it is listed after it alphabetically, prefer zero-padded names",
+                    names[i], alphabetical[i]
+                ),
+            );
+        }
+
+        Ok(())
+    }
+}
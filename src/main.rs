@@ -1,14 +1,50 @@
-use crate::utils::sorted_subfolders;
-use clap::Parser;
-use items::command::Command;
+use analysis::ScopeAnalysis;
+use cache::FolderCache;
+use clap::{Args, Parser, Subcommand};
+use compare::Checkout;
+use context::Context;
+use coverage::{Coverage, CoverageFormat};
+use input::{InputPolicy, InputQueue};
+use interpreter::Interpreter;
+use numeric::NumericMode;
+use profile::Profiler;
 use scope::Scope;
-use std::{cell::RefCell, rc::Rc};
-use transpile::Transpile;
+use signal_hook::consts::SIGINT;
+use snapshot::Snapshot;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use transpile::Target;
+use utils::input_error;
 
+mod analysis;
+mod cache;
+mod compare;
+mod context;
+mod coverage;
+mod diff;
+mod disasm;
+mod format;
+mod graph;
+mod input;
+mod interpreter;
 mod items;
+mod lint;
+mod numeric;
+mod optimizer;
+mod profile;
 mod scope;
+mod snapshot;
 mod transpile;
+mod tree;
 mod utils;
+mod watch;
 
 #[derive(Parser)]
 #[command(
@@ -17,54 +53,775 @@ mod utils;
     about = "Interpreter and transpiler for esolang Folders 📂"
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// Allow commands and expressions beyond the base language
+    #[arg(long, global = true)]
+    extensions: bool,
+
+    /// Give Bool values instead of 1 or 0 from comparisons and logical operations
+    #[arg(long, global = true, requires = "extensions")]
+    booleans: bool,
+
+    /// Maximum number of nested procedure calls, high values may overflow the stack
+    #[arg(long, global = true, default_value_t = context::MAX_CALL_DEPTH)]
+    max_call_depth: usize,
+
+    /// Handling of Int overflows and divisions by zero
+    #[arg(long, global = true, value_enum, default_value_t = NumericMode::Wrapping)]
+    numeric: NumericMode,
+
+    /// Report what is being done on stderr, repeat for more details
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+/// Options of the commands reading a program before running or transpiling it
+#[derive(Args)]
+struct ProgramArgs {
     /// Folder path to operate on
     folder: String,
 
-    /// Transpile folder to actual source code
-    #[arg(short, long)]
-    transpile: bool,
+    /// Fold constant expressions and drop unreachable branches, the program
+    /// must not modify its own folders while running
+    #[arg(short = 'O', long)]
+    optimize: bool,
+
+    /// Read folders from disk every time instead of caching them, for programs
+    /// modifying their own folders while running
+    #[arg(long)]
+    no_cache: bool,
 }
 
-fn main() -> std::io::Result<()> {
-    let cli = Cli::parse();
+/// Hit counts and timings of the folders run, printed to stderr once the program ends
+#[derive(Args)]
+struct ProfileArgs {
+    /// Print how many times every command and expression folder ran and the time spent in it
+    #[arg(long)]
+    profile: bool,
+
+    /// Also write the profile to this file as folded stacks, read by flamegraph tools
+    #[arg(long, value_name = "FILE", requires = "profile")]
+    profile_folded: Option<PathBuf>,
+}
+
+/// Commands run and 'if' bodies entered or skipped, written once the program ends
+#[derive(Args)]
+struct CoverageArgs {
+    /// File the coverage report is written to, its lines numbered as in the 'disasm' listing
+    #[arg(long, value_name = "FILE")]
+    coverage: Option<PathBuf>,
+
+    /// Format of the coverage report
+    #[arg(long, value_enum, default_value_t, requires = "coverage")]
+    coverage_format: CoverageFormat,
+}
+
+/// Where 'input' commands read from, stdin by default
+#[derive(Args)]
+struct InputArgs {
+    /// File whose lines are read by 'input' commands, before the lines given with --input
+    #[arg(long)]
+    input_file: Option<PathBuf>,
+
+    /// Line read by an 'input' command, can be repeated
+    #[arg(long = "input", value_name = "LINE")]
+    inputs: Vec<String>,
+
+    /// Fail when the program asks for more input than provided instead of
+    /// reading empty lines
+    #[arg(long)]
+    strict_input: bool,
+
+    /// How read lines are turned into values
+    #[arg(long, value_enum, default_value_t = InputPolicy::Infer)]
+    input_policy: InputPolicy,
+}
+
+impl InputArgs {
+    fn queue(&self) -> std::io::Result<InputQueue> {
+        if self.input_file.is_none() && self.inputs.is_empty() {
+            return Ok(InputQueue::new(None, self.strict_input, self.input_policy));
+        }
+
+        let mut lines = vec![];
+        if let Some(path) = &self.input_file {
+            lines.extend(std::fs::read_to_string(path)?.lines().map(String::from));
+        }
+        lines.extend(self.inputs.iter().cloned());
+
+        Ok(InputQueue::new(
+            Some(lines),
+            self.strict_input,
+            self.input_policy,
+        ))
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Interpret a program
+    Run {
+        #[command(flatten)]
+        program: ProgramArgs,
+
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// File the state of the program is saved to on SIGINT, which stops it,
+        /// or on SIGUSR1, which lets it run
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+
+        /// Continue the program from a snapshot instead of running it from the start
+        #[arg(long)]
+        resume: Option<PathBuf>,
+
+        #[command(flatten)]
+        profile: ProfileArgs,
+
+        #[command(flatten)]
+        coverage: CoverageArgs,
+
+        /// Run the program again with the same input each time its folders change,
+        /// showing how its listing changed
+        #[arg(long, conflicts_with_all = ["snapshot", "resume"])]
+        watch: bool,
+    },
+
+    /// Transpile a program to actual source code
+    Transpile {
+        #[command(flatten)]
+        program: ProgramArgs,
+
+        /// Language emitted by the transpiler
+        #[arg(long, value_enum, default_value_t = Target::Rust)]
+        target: Target,
+
+        /// File to write the source code to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Report the errors found before running a program, without running it
+    Check {
+        #[command(flatten)]
+        program: ProgramArgs,
+    },
+
+    /// List the commands of a program as pseudo-code
+    Disasm {
+        /// Folder path to operate on
+        folder: String,
+
+        /// Also show the folder behind each command
+        #[arg(long)]
+        folders: bool,
+    },
+
+    /// Report how a program changed, command by command. Exits with 1 when
+    /// the programs differ, unless run as a git diff driver
+    Diff {
+        /// Programs before and after the changes, folders or '<revision>:<folder>'
+        /// read from git. Git diff drivers are given 7 arguments instead :
+        /// 'path old-file old-hex old-mode new-file new-hex new-mode', followed
+        /// by 'new-path rename-header' for renamed files
+        #[arg(num_args = 2..=9, value_name = "PROGRAM", required = true)]
+        programs: Vec<String>,
+
+        /// As a git diff driver, number of leading folders of the changed
+        /// paths naming their program
+        #[arg(long, default_value_t = 1)]
+        root_depth: usize,
+
+        /// As a git diff driver, revision of the programs before the changes
+        #[arg(long, default_value = "HEAD")]
+        old_revision: String,
+
+        /// As a git diff driver, revision of the programs after the changes,
+        /// the working tree by default
+        #[arg(long)]
+        new_revision: Option<String>,
+    },
+
+    /// Transpile a program to Rust and compile it with rustc
+    Build {
+        #[command(flatten)]
+        program: ProgramArgs,
+
+        /// Path of the executable, named after the program folder by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Rename the folders of a program to canonical names, keeping its execution order
+    Fmt {
+        /// Folder path to operate on
+        folder: String,
+
+        /// Only list the folders that are not formatted, fails if there is any
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Warn about folder layouts that are ambiguous or easy to break
+    Lint {
+        /// Folder path to operate on
+        folder: String,
+    },
+
+    /// Render the syntax tree of a program
+    Graph {
+        /// Folder path to operate on
+        folder: String,
+
+        #[arg(long, value_enum, default_value_t = graph::Format::Dot)]
+        format: graph::Format,
+
+        /// Also show the folder behind each node
+        #[arg(long)]
+        folders: bool,
+    },
+}
+
+impl Cli {
+    /// Print `message` on stderr when verbosity reaches `level`
+    fn log(&self, level: u8, message: impl std::fmt::Display) {
+        if self.verbose >= level {
+            eprintln!("{message}");
+        }
+    }
+
+    /// Context of the subcommands only reading a program
+    fn context(&self) -> Rc<Context> {
+        Rc::new(Context {
+            extensions: self.extensions,
+            booleans: self.booleans,
+            numeric: self.numeric,
+            ..Default::default()
+        })
+    }
+
+    /// Context used to run or transpile the program of `args`
+    fn program_context(&self, args: &ProgramArgs) -> std::io::Result<Rc<Context>> {
+        Ok(Rc::new(self.run_context(args, InputQueue::default())?))
+    }
+
+    /// Context used to run the program of `args`, reading `input`
+    fn run_context(&self, args: &ProgramArgs, input: InputQueue) -> std::io::Result<Context> {
+        let optimizations = if args.optimize {
+            self.log(1, format!("optimizing {}", args.folder));
+            optimizer::Optimizations::new(&args.folder, &self.context())?
+        } else {
+            Default::default()
+        };
+
+        Ok(Context {
+            optimizations,
+            cache: FolderCache::new(!args.no_cache),
+            extensions: self.extensions,
+            booleans: self.booleans,
+            root: args.folder.clone(),
+            max_call_depth: self.max_call_depth,
+            input,
+            numeric: self.numeric,
+            ..Default::default()
+        })
+    }
+}
+
+fn diff_programs(old: &str, new: &str, context: &Rc<Context>) -> std::io::Result<()> {
+    let old_checkout = Checkout::new(old)?;
+    let new_checkout = Checkout::new(new)?;
+
+    let differences = compare::compare(
+        old_checkout
+            .as_ref()
+            .map_or(old, |checkout| &checkout.folder),
+        new_checkout
+            .as_ref()
+            .map_or(new, |checkout| &checkout.folder),
+        context,
+    )?;
+
+    for difference in &differences {
+        let shown = [&old_checkout, &new_checkout]
+            .into_iter()
+            .flatten()
+            .fold(difference.clone(), |text, checkout| checkout.show(&text));
+        println!("{shown}");
+    }
+
+    // temporary folders are removed before exiting
+    drop((old_checkout, new_checkout));
+    if !differences.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Git diff driver : the programs holding the changed `path`, and `new_path`
+/// when it was renamed, are compared between both revisions, once for all
+/// their changed files. Neither differences nor invalid programs stop git.
+fn diff_driver(
+    path: &str,
+    new_path: Option<&str>,
+    root_depth: usize,
+    old_revision: &str,
+    new_revision: Option<&str>,
+    context: &Rc<Context>,
+) -> std::io::Result<()> {
+    std::env::set_current_dir(compare::top_level()?)?;
+
+    let mut roots: Vec<String> = [Some(path), new_path]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| compare::program_root(path, root_depth))
+        .collect();
+    roots.dedup();
+
+    for root in compare::first_comparisons(roots)? {
+        let new_spec = match new_revision {
+            Some(revision) => format!("{revision}:{root}"),
+            None => root.clone(),
+        };
+        let header = format!("folders diff {old_revision}:{root} {new_spec}");
+        match driver_differences(&root, old_revision, new_revision, context) {
+            Ok(differences) if differences.is_empty() => {}
+            Ok(differences) => println!("{header}\n{}", differences.join("\n")),
+            Err(err) => println!("{header}\nerror: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Differences of the program in `root`, shown with the revisions
+fn driver_differences(
+    root: &str,
+    old_revision: &str,
+    new_revision: Option<&str>,
+    context: &Rc<Context>,
+) -> std::io::Result<Vec<String>> {
+    let old_checkout = side(old_revision, root)?;
+    let new_checkout = match new_revision {
+        Some(revision) => Some(side(revision, root)?),
+        None if Path::new(root).is_dir() => None,
+        None => Some(Checkout::empty(root)?),
+    };
+    let new_folder = new_checkout
+        .as_ref()
+        .map_or(root, |checkout| &checkout.folder);
+
+    let show = |text: String| {
+        [Some(&old_checkout), new_checkout.as_ref()]
+            .into_iter()
+            .flatten()
+            .fold(text, |text, checkout| checkout.show(&text))
+    };
+
+    match compare::compare(&old_checkout.folder, new_folder, context) {
+        Ok(differences) => Ok(differences.into_iter().map(show).collect()),
+        Err(err) => Err(input_error(show(err.to_string()))),
+    }
+}
 
-    let global_scope = Rc::new(RefCell::new(Scope::new(None)));
-    let cmd_folders = sorted_subfolders(&cli.folder)?;
+/// Program in `root` at `revision`, empty when the revision does not have it
+fn side(revision: &str, root: &str) -> std::io::Result<Checkout> {
+    let checkout = Checkout::revision(revision, root)?;
+    if Path::new(&checkout.folder).is_dir() {
+        Ok(checkout)
+    } else {
+        Checkout::empty(&format!("{revision}:{root}"))
+    }
+}
+
+fn format_program(folder: &str, check: bool, context: &Rc<Context>) -> std::io::Result<()> {
+    let plans = format::plan(folder, context)?;
 
-    // used for translation
-    let mut lines: Vec<String> = vec![];
-    let mut using_print = false;
+    if !check {
+        return format::apply(&plans);
+    }
+
+    let mut formatted = true;
+    for plan in plans.iter().filter(|plan| !plan.is_formatted()) {
+        for (name, canonical) in plan
+            .names
+            .iter()
+            .filter(|(name, canonical)| name != canonical)
+        {
+            let path = std::path::Path::new(&plan.folder).join(name);
+            println!("{} : should be named '{canonical}'", path.display());
+        }
+        formatted = false;
+    }
+
+    if !formatted {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
 
-    for folder in cmd_folders {
-        let mut cmd = Command::new(&folder, &global_scope)?;
+fn run(
+    cli: &Cli,
+    program: &ProgramArgs,
+    input: &InputArgs,
+    snapshot: Option<&Path>,
+    resume: Option<&Path>,
+    profile: &ProfileArgs,
+    coverage: &CoverageArgs,
+) -> std::io::Result<()> {
+    let resumed = resume.map(Snapshot::load).transpose()?;
 
-        if cli.transpile {
-            lines.extend(
-                cmd.transpile()?
-                    .split("\n")
-                    .map(|line| line.to_owned())
-                    .into_iter(),
+    // lines left when the snapshot was taken, unless new ones are given
+    let mut queue = input.queue()?;
+    if let Some(resumed) = &resumed {
+        if queue.pending().is_none() && resumed.input.is_some() {
+            queue = InputQueue::new(
+                resumed.input.clone(),
+                input.strict_input,
+                input.input_policy,
             );
+        }
+    }
 
-            if lines.iter().find(|line| line.contains("print")).is_some() {
-                using_print = true;
+    let context = Rc::new(Context {
+        profiler: Profiler::new(profile.profile),
+        coverage: Coverage::new(coverage.coverage.is_some()),
+        ..cli.run_context(program, queue)?
+    });
+    let mut interpreter = match &resumed {
+        Some(resumed) => {
+            if !resumed.is_taken_from(&program.folder) {
+                return Err(input_error(format!(
+                    "{} : snapshot taken while running {}",
+                    program.folder, resumed.program
+                )));
             }
-        } else {
-            cmd.run()?;
+            cli.log(1, format!("resuming {}", program.folder));
+            resumed.restore(&context)?
+        }
+        None => {
+            cli.log(1, format!("running {}", program.folder));
+            let global_scope = Rc::new(RefCell::new(Scope::new(None)));
+            Interpreter::new(&program.folder, global_scope, &context)?
         }
+    };
+
+    let result = execute(cli, &mut interpreter, &context, snapshot);
+
+    // errors of the program come first, reports are written anyway
+    let reported = report(cli, &context, profile, coverage);
+    let stopped = result?;
+    reported?;
+
+    if stopped {
+        std::process::exit(130);
     }
 
-    if cli.transpile {
-        if using_print {
-            println!("use std::io::Write;\n");
+    Ok(())
+}
+
+/// Run the program, then again each time its folders change. Lines read by a
+/// run are given again to the next one
+fn watch(
+    cli: &Cli,
+    program: &ProgramArgs,
+    input: &InputArgs,
+    profile: &ProfileArgs,
+    coverage: &CoverageArgs,
+) -> std::io::Result<()> {
+    let mut input = input.queue()?;
+    let mut listing: Option<Vec<String>> = None;
+
+    loop {
+        match disasm::disassemble(&program.folder, &cli.context()) {
+            Ok(lines) => {
+                let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+                if let Some(last) = &listing {
+                    let hunks = diff::unified(last, &lines, 3);
+                    if hunks.is_empty() {
+                        cli.log(1, "listing unchanged");
+                    } else {
+                        eprintln!("--- {} (last run)", program.folder);
+                        eprintln!("+++ {}", program.folder);
+                        for line in hunks {
+                            eprintln!("{line}");
+                        }
+                    }
+                }
+                listing = Some(lines);
+            }
+            Err(err) => eprintln!("Error: {err}"),
         }
 
-        println!("fn main() {{");
-        for line in lines {
-            println!("\t{line}");
+        cli.log(1, format!("running {}", program.folder));
+        let context = Rc::new(Context {
+            profiler: Profiler::new(profile.profile),
+            coverage: Coverage::new(coverage.coverage.is_some()),
+            ..cli.run_context(program, input.replay())?
+        });
+
+        let global_scope = Rc::new(RefCell::new(Scope::new(None)));
+        let result = Interpreter::new(&program.folder, global_scope, &context)
+            .and_then(|mut interpreter| interpreter.run())
+            .and_then(|_| report(cli, &context, profile, coverage));
+        if let Err(err) = result {
+            eprintln!("Error: {err}");
         }
-        println!("}}");
+        input = context.input.replay();
+
+        // folders changed by the program itself do not make it run again
+        cli.log(1, format!("waiting for changes in {}", program.folder));
+        watch::wait_for_change(&program.folder);
+    }
+}
+
+/// Print the profile and write the coverage report of a run, when asked to
+fn report(
+    cli: &Cli,
+    context: &Rc<Context>,
+    profile: &ProfileArgs,
+    coverage: &CoverageArgs,
+) -> std::io::Result<()> {
+    if profile.profile {
+        context.profiler.finish();
+        eprint!(
+            "{}",
+            context
+                .profiler
+                .report(&context.root, context.cache.reads())
+        );
+
+        if let Some(folded) = &profile.profile_folded {
+            std::fs::write(folded, context.profiler.folded_stacks())?;
+            cli.log(1, format!("folded stacks written to {}", folded.display()));
+        }
+    }
+
+    if let Some(output) = &coverage.coverage {
+        let listing = disasm::disassemble(&context.root, context)?;
+        let report = context
+            .coverage
+            .report(coverage.coverage_format, &context.root, &listing);
+
+        std::fs::write(output, report)?;
+        cli.log(1, format!("coverage written to {}", output.display()));
     }
 
     Ok(())
 }
+
+/// Run the program, saving its state to `snapshot` on SIGINT and SIGUSR1.
+/// Returns whether SIGINT stopped it
+fn execute(
+    cli: &Cli,
+    interpreter: &mut Interpreter,
+    context: &Context,
+    snapshot: Option<&Path>,
+) -> std::io::Result<bool> {
+    let Some(snapshot) = snapshot else {
+        interpreter.run()?;
+        return Ok(false);
+    };
+
+    // a second SIGINT stops the program right away
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register_conditional_default(SIGINT, interrupted.clone())?;
+    signal_hook::flag::register(SIGINT, interrupted.clone())?;
+
+    let requested = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGUSR1, requested.clone())?;
+
+    while !interpreter.is_done() {
+        // a program waiting for a line of stdin is stopped before its 'input' command
+        let waited = !interpreter.reads_input_next()? || context.input.wait(&interrupted);
+        if waited {
+            interpreter.step()?;
+        }
+
+        let interrupted = interrupted.load(Ordering::Relaxed);
+        if interrupted || requested.swap(false, Ordering::Relaxed) {
+            Snapshot::capture(interpreter, context)?.save(snapshot)?;
+            cli.log(1, format!("snapshot saved to {}", snapshot.display()));
+        }
+
+        if interrupted {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn transpile_program(
+    folder: &str,
+    target: Target,
+    context: &Rc<Context>,
+) -> std::io::Result<String> {
+    let analysis = ScopeAnalysis::new(folder, context)?;
+
+    match target {
+        Target::Rust => transpile::rust::transpile(folder, &analysis, context),
+        Target::Wat => transpile::wat::transpile(folder, &analysis, context),
+    }
+}
+
+fn build(cli: &Cli, args: &ProgramArgs, output: Option<&Path>) -> std::io::Result<()> {
+    let context = cli.program_context(args)?;
+    let source = transpile_program(&args.folder, Target::Rust, &context)?;
+
+    let output = match output {
+        Some(output) => output.to_owned(),
+        None => Path::new(&args.folder)
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("program")),
+    };
+
+    let source_path = std::env::temp_dir().join(format!("folders-build-{}.rs", std::process::id()));
+    std::fs::write(&source_path, source + "\n")?;
+    cli.log(2, format!("source written to {}", source_path.display()));
+
+    let mut rustc = std::process::Command::new("rustc");
+    rustc
+        .args(["-O", "--edition", "2021", "-o"])
+        .arg(&output)
+        .arg(&source_path);
+    // generated code is not idiomatic, its warnings are only noise
+    if cli.verbose < 2 {
+        rustc.args(["-A", "warnings"]);
+    }
+
+    cli.log(1, format!("compiling {}", output.display()));
+    let status = rustc.status();
+    std::fs::remove_file(&source_path)?;
+
+    if !status?.success() {
+        return Err(input_error(format!(
+            "{} : rustc could not compile the transpiled program",
+            args.folder
+        )));
+    }
+
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Commands::Run {
+            program,
+            input,
+            profile,
+            coverage,
+            watch: true,
+            ..
+        } => watch(&cli, program, input, profile, coverage),
+
+        Commands::Run {
+            program,
+            input,
+            snapshot,
+            resume,
+            profile,
+            coverage,
+            watch: false,
+        } => run(
+            &cli,
+            program,
+            input,
+            snapshot.as_deref(),
+            resume.as_deref(),
+            profile,
+            coverage,
+        ),
+
+        Commands::Transpile {
+            program,
+            target,
+            output,
+        } => {
+            let context = cli.program_context(program)?;
+            cli.log(1, format!("transpiling {} to {target:?}", program.folder));
+            let source = transpile_program(&program.folder, *target, &context)?;
+
+            match output {
+                Some(output) => std::fs::write(output, source + "\n"),
+                None => {
+                    println!("{source}");
+                    Ok(())
+                }
+            }
+        }
+
+        Commands::Check { program } => {
+            let context = cli.program_context(program)?;
+            ScopeAnalysis::new(&program.folder, &context)?;
+            cli.log(1, format!("{} : no error found", program.folder));
+            Ok(())
+        }
+
+        Commands::Disasm { folder, folders } => {
+            for line in disasm::disassemble(folder, &cli.context())? {
+                match &line.folder {
+                    Some(command_folder) if *folders => {
+                        println!("{:<48} # {command_folder}", line.to_string())
+                    }
+                    _ => println!("{line}"),
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Diff {
+            programs,
+            root_depth,
+            old_revision,
+            new_revision,
+        } => match programs.as_slice() {
+            [old, new] => diff_programs(old, new, &cli.context()),
+            [path, _, _, _, _, _, _] | [path, _, _, _, _, _, _, _, _] => diff_driver(
+                path,
+                programs.get(7).map(String::as_str),
+                *root_depth,
+                old_revision,
+                new_revision.as_deref(),
+                &cli.context(),
+            ),
+            _ => Err(input_error(format!(
+                "expected 2 programs, or the 7 or 9 arguments of a git diff driver, got {}",
+                programs.len()
+            ))),
+        },
+
+        Commands::Build { program, output } => build(&cli, program, output.as_deref()),
+
+        Commands::Fmt { folder, check } => format_program(folder, *check, &cli.context()),
+
+        Commands::Lint { folder } => {
+            for warning in lint::lint(folder, &cli.context())? {
+                println!("warning: {warning}");
+            }
+            Ok(())
+        }
+
+        Commands::Graph {
+            folder,
+            format,
+            folders,
+        } => {
+            println!(
+                "{}",
+                graph::graph(folder, *format, *folders, &cli.context())?
+            );
+            Ok(())
+        }
+    }
+}
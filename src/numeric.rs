@@ -0,0 +1,70 @@
+use crate::items::expression::ExpressionType;
+
+/// How Int arithmetic handles results out of the i32 range, and divisions by zero
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum NumericMode {
+    /// results wrap around the i32 range, Float divisions by zero give infinities or NaN
+    #[default]
+    Wrapping,
+
+    /// results out of the i32 range and divisions by zero are errors
+    Checked,
+
+    /// results are clamped to the i32 range, Float divisions by zero give infinities or NaN
+    Saturating,
+}
+
+impl NumericMode {
+    /// Int operations whose result can leave the i32 range
+    pub fn applies_to(operator: ExpressionType) -> bool {
+        matches!(
+            operator,
+            ExpressionType::Add
+                | ExpressionType::Substract
+                | ExpressionType::Multiply
+                | ExpressionType::IntDivide
+        )
+    }
+
+    /// Result of an Int operation, None when it overflows in checked mode.
+    /// Divisors must not be zero
+    pub fn apply(self, operator: ExpressionType, a: i32, b: i32) -> Option<i32> {
+        let (a, b) = (a as i64, b as i64);
+        let exact = match operator {
+            ExpressionType::Add => a + b,
+            ExpressionType::Substract => a - b,
+            ExpressionType::Multiply => a * b,
+            ExpressionType::IntDivide => a / b,
+            _ => unreachable!("only arithmetic operations can overflow"),
+        };
+
+        match self {
+            Self::Wrapping => Some(exact as i32),
+            Self::Checked => i32::try_from(exact).ok(),
+            Self::Saturating => Some(exact.clamp(i32::MIN as i64, i32::MAX as i64) as i32),
+        }
+    }
+
+    /// Rust code applying an Int operation to `left` and `right`, panicking on overflow in checked mode
+    pub fn transpile(self, operator: ExpressionType, left: &str, right: &str) -> String {
+        let name = match operator {
+            ExpressionType::Add => "add",
+            ExpressionType::Substract => "sub",
+            ExpressionType::Multiply => "mul",
+            ExpressionType::IntDivide => "div",
+            _ => unreachable!("{operator:?} is not an Int operation"),
+        };
+
+        match self {
+            Self::Wrapping => format!("i32::wrapping_{name}({left}, {right})"),
+            // checked divisions also give None when dividing by zero
+            Self::Checked if operator == ExpressionType::IntDivide => format!(
+                "i32::checked_div({left}, {right}).expect(\"Int overflow or division by zero\")"
+            ),
+            Self::Checked => {
+                format!("i32::checked_{name}({left}, {right}).expect(\"Int overflow\")")
+            }
+            Self::Saturating => format!("i32::saturating_{name}({left}, {right})"),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    analysis::binary_type,
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+        variable::Variable,
+    },
+    scope::Scope,
+    utils::{input_error, sorted_subfolders},
+};
+
+/// Constant folding over a program, indexed by folder.
+///
+/// The folder tree is read once, programs changing their own tree at runtime
+/// with a reachable 'write', 'mkdir' or 'rmdir' are rejected.
+#[derive(Default)]
+pub struct Optimizations {
+    /// value of the expressions made of literals only
+    folded: HashMap<String, Variable>,
+
+    /// 'if' and 'while' commands whose condition is constant
+    conditions: HashMap<String, bool>,
+
+    /// commands that never run anything
+    dead: HashSet<String>,
+}
+
+impl Optimizations {
+    pub fn new(folder: &str, context: &Rc<Context>) -> std::io::Result<Self> {
+        let mut optimizer = Optimizer {
+            optimizations: Optimizations::default(),
+            scope: Rc::new(RefCell::new(Scope::new(None))),
+            context: context.clone(),
+        };
+        optimizer.block(folder)?;
+
+        Ok(optimizer.optimizations)
+    }
+
+    pub fn folded(&self, folder: &str) -> Option<&Variable> {
+        self.folded.get(folder)
+    }
+
+    /// Truthiness of the condition of an 'if' or 'while' command, if known before running
+    pub fn constant_condition(&self, folder: &str) -> Option<bool> {
+        self.conditions.get(folder).copied()
+    }
+
+    /// Command that can be dropped : an 'if' without 'else' or a 'while' never entered
+    pub fn is_dead(&self, folder: &str) -> bool {
+        self.dead.contains(folder)
+    }
+}
+
+struct Optimizer {
+    optimizations: Optimizations,
+
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl Optimizer {
+    fn block(&mut self, folder: &str) -> std::io::Result<()> {
+        for subfolder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&subfolder, &self.scope, &self.context)?;
+            self.command(&cmd)?;
+        }
+
+        Ok(())
+    }
+
+    fn command(&mut self, cmd: &Command) -> std::io::Result<()> {
+        match cmd.command_type {
+            CommandType::Let | CommandType::Print => {
+                self.expression(&cmd.expression()?)?;
+            }
+
+            CommandType::If | CommandType::While => {
+                let condition = self
+                    .expression(&cmd.expression()?)?
+                    .map(|value| value.is_truthy());
+
+                if let Some(condition) = condition {
+                    self.optimizations
+                        .conditions
+                        .insert(cmd.folder().to_owned(), condition);
+                }
+
+                // unreachable bodies are left as they are
+                if condition != Some(false) {
+                    self.block(cmd.body_folder()?)?;
+                }
+
+                match cmd.else_folder() {
+                    Some(else_folder) if condition != Some(true) => self.block(else_folder)?,
+                    Some(_) => {}
+                    None if condition == Some(false) => {
+                        self.optimizations.dead.insert(cmd.folder().to_owned());
+                    }
+                    None => {}
+                }
+            }
+
+            // folded values would be read from folders that may have changed since
+            CommandType::Write | CommandType::CreateFolder | CommandType::RemoveFolder => {
+                return Err(input_error(format!(
+                    "{} : {} command modifies the program folders, it can not be optimized",
+                    cmd.folder(),
+                    cmd.command_type.as_str()
+                )));
+            }
+
+            CommandType::Return => {
+                self.expression(&cmd.expression()?)?;
+            }
+
+            CommandType::Define => {
+                self.block(cmd.body_folder()?)?;
+            }
+
+            CommandType::Call => {
+                self.arguments(cmd.arguments_folder()?)?;
+            }
+
+            CommandType::Declare | CommandType::Input => {}
+        }
+
+        Ok(())
+    }
+
+    fn arguments(&mut self, folder: &str) -> std::io::Result<()> {
+        for argument in sorted_subfolders(folder)? {
+            self.expression(&Expression::new(&argument, &self.scope, &self.context)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fold an expression, returns its value if it is constant
+    fn expression(&mut self, exp: &Expression) -> std::io::Result<Option<Variable>> {
+        let value = match exp.expression_type {
+            ExpressionType::Variable => None,
+
+            ExpressionType::LiteralValue => Some(exp.get_literal_value()?),
+
+            ExpressionType::Call => {
+                self.arguments(exp.arguments_folder())?;
+                None
+            }
+
+            // arrays only come from variables, array expressions are never constant
+            exp_type if exp_type.is_sequence() => {
+                for operand in exp.sequence_operands()? {
+                    self.expression(&operand)?;
+                }
+                None
+            }
+
+            // conversions that would fail are left to the runtime
+            ExpressionType::Cast => {
+                let target = exp.target_type()?;
+                self.expression(&exp.cast_operand()?)?
+                    .and_then(|value| value.cast(target))
+            }
+
+            ExpressionType::Not => self
+                .expression(&exp.operand()?)?
+                .map(|a| exp.evaluate_unary(a)),
+
+            _ => {
+                let (first, second) = exp.operands()?;
+                let a = self.expression(&first)?;
+                let b = self.expression(&second)?;
+
+                match (a, b) {
+                    // operations that would fail, like overflows in checked mode,
+                    // are left to the runtime
+                    (Some(a), Some(b))
+                        if binary_type(
+                            exp.expression_type,
+                            a.get_type(),
+                            b.get_type(),
+                            &self.context,
+                        )
+                        .is_some() =>
+                    {
+                        exp.evaluate(a, b).ok()
+                    }
+                    _ => None,
+                }
+            }
+        };
+
+        if let Some(value) = &value {
+            self.optimizations
+                .folded
+                .insert(exp.folder().to_owned(), value.clone());
+        }
+
+        Ok(value)
+    }
+}
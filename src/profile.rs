@@ -0,0 +1,379 @@
+//! Hit counts and timings of the command and expression folders of a run.
+//!
+//! Every folder being run is kept on a stack: the time between two changes of
+//! the stack is given to its innermost folder, and to the whole stack for the
+//! folded stacks read by flamegraph tools.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// number of loops listed in reports
+const HOTTEST_LOOPS: usize = 10;
+
+#[derive(Default, Clone, Copy)]
+pub struct Entry {
+    /// number of times the folder was run
+    pub hits: usize,
+
+    /// time spent running the folder, nested folders included
+    pub time: Duration,
+
+    /// number of times the body of a 'while' command was run
+    pub iterations: usize,
+}
+
+struct Running {
+    folder: String,
+
+    start: Instant,
+
+    /// folders of the stack up to this one, joined by ';'
+    path: String,
+
+    /// the folder was not running already, recursive runs are timed by the outermost one
+    outermost: bool,
+}
+
+#[derive(Default)]
+struct State {
+    /// folders being run, innermost last
+    stack: Vec<Running>,
+
+    /// start of the time not given to any folder yet
+    last: Option<Instant>,
+
+    /// number of runs of each folder on the stack
+    depths: HashMap<String, usize>,
+
+    /// end of the run, once finished
+    finished: Option<Instant>,
+
+    entries: HashMap<String, Entry>,
+
+    /// time spent with exactly this stack, folders joined by ';'
+    stacks: HashMap<String, Duration>,
+}
+
+/// Profile of a run, does nothing unless enabled
+pub struct Profiler {
+    enabled: bool,
+
+    /// start of the run, reading the program included
+    started: Instant,
+
+    state: RefCell<State>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new(false)
+    }
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Profiler {
+            enabled,
+            started: Instant::now(),
+            state: RefCell::default(),
+        }
+    }
+
+    /// `folder` starts running
+    pub fn enter(&self, folder: &str) {
+        if self.enabled {
+            self.push(folder, true);
+        }
+    }
+
+    /// `folder` was already running when the run was resumed, it is not counted again
+    pub fn reopen(&self, folder: &str) {
+        if self.enabled {
+            self.push(folder, false);
+        }
+    }
+
+    /// The innermost folder is done
+    pub fn exit(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        self.flush();
+
+        let mut state = self.state.borrow_mut();
+        let Some(running) = state.stack.pop() else {
+            return;
+        };
+
+        if let Some(depth) = state.depths.get_mut(&running.folder) {
+            *depth -= 1;
+            if *depth == 0 {
+                state.depths.remove(&running.folder);
+            }
+        }
+        if running.outermost {
+            state.entries.entry(running.folder).or_default().time += running.start.elapsed();
+        }
+    }
+
+    /// The body of the 'while' command in `folder` runs once more
+    pub fn iterate(&self, folder: &str) {
+        if self.enabled {
+            let mut state = self.state.borrow_mut();
+            state
+                .entries
+                .entry(folder.to_owned())
+                .or_default()
+                .iterations += 1;
+        }
+    }
+
+    /// Close the folders still running, after an error or a snapshot, and end the run
+    pub fn finish(&self) {
+        while !self.state.borrow().stack.is_empty() {
+            self.exit();
+        }
+
+        self.state
+            .borrow_mut()
+            .finished
+            .get_or_insert_with(Instant::now);
+    }
+
+    /// Time from the start of the run to its end, or until now if it is not finished
+    pub fn total(&self) -> Duration {
+        self.state
+            .borrow()
+            .finished
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.started)
+    }
+
+    /// Every folder run, longest first
+    pub fn entries(&self) -> Vec<(String, Entry)> {
+        let mut entries: Vec<(String, Entry)> = self
+            .state
+            .borrow()
+            .entries
+            .iter()
+            .map(|(folder, entry)| (folder.clone(), *entry))
+            .collect();
+        entries.sort_by(|(a, a_entry), (b, b_entry)| {
+            b_entry.time.cmp(&a_entry.time).then_with(|| a.cmp(b))
+        });
+
+        entries
+    }
+
+    /// Hottest loops and time spent in every folder, `reads` being the number
+    /// of directories read and the time spent reading them
+    pub fn report(&self, root: &str, reads: (usize, Duration)) -> String {
+        let entries = self.entries();
+        let total = self.total();
+
+        let mut report = format!(
+            "profile of {root} : {total:.3?} in total, {} directories read in {:.3?}\n",
+            reads.0, reads.1
+        );
+
+        let mut loops: Vec<&(String, Entry)> = entries
+            .iter()
+            .filter(|(_, entry)| entry.iterations > 0)
+            .collect();
+        loops.truncate(HOTTEST_LOOPS);
+
+        if !loops.is_empty() {
+            report += &format!(
+                "\nhottest loops :\n{:>10} {:>12}  folder\n",
+                "iterations", "time"
+            );
+            for (folder, entry) in loops {
+                report += &format!("{:>10} {:>12.3?}  {folder}\n", entry.iterations, entry.time);
+            }
+        }
+
+        report += &format!("\nfolders :\n{:>10} {:>12}  folder\n", "hits", "time");
+        for (folder, entry) in &entries {
+            report += &format!("{:>10} {:>12.3?}  {folder}\n", entry.hits, entry.time);
+        }
+
+        report
+    }
+
+    /// One line per stack with the microseconds spent in it, as read by flamegraph tools
+    pub fn folded_stacks(&self) -> String {
+        let state = self.state.borrow();
+        let mut stacks: Vec<(&String, &Duration)> = state.stacks.iter().collect();
+        stacks.sort();
+
+        stacks
+            .into_iter()
+            .map(|(stack, time)| format!("{stack} {}\n", time.as_micros()))
+            .collect()
+    }
+
+    fn push(&self, folder: &str, counted: bool) {
+        self.flush();
+
+        let mut state = self.state.borrow_mut();
+        if counted {
+            state.entries.entry(folder.to_owned()).or_default().hits += 1;
+        }
+        let path = match state.stack.last() {
+            Some(outer) => format!("{};{folder}", outer.path),
+            None => folder.to_owned(),
+        };
+        let depth = state.depths.entry(folder.to_owned()).or_default();
+        *depth += 1;
+        let outermost = *depth == 1;
+
+        state.stack.push(Running {
+            folder: folder.to_owned(),
+            start: Instant::now(),
+            path,
+            outermost,
+        });
+    }
+
+    /// Give the time since the last change of the stack to the current stack
+    fn flush(&self) {
+        let now = Instant::now();
+        let state = &mut *self.state.borrow_mut();
+
+        let Some(last) = state.last.replace(now) else {
+            return;
+        };
+
+        if let Some(running) = state.stack.last() {
+            match state.stacks.get_mut(&running.path) {
+                Some(time) => *time += now - last,
+                None => {
+                    state.stacks.insert(running.path.clone(), now - last);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAUSE: Duration = Duration::from_millis(20);
+
+    fn time(profiler: &Profiler, folder: &str) -> Duration {
+        profiler.state.borrow().entries[folder].time
+    }
+
+    #[test]
+    fn recursive_runs_are_timed_once() {
+        let profiler = Profiler::new(true);
+
+        profiler.enter("proc");
+        std::thread::sleep(PAUSE);
+        profiler.enter("proc");
+        std::thread::sleep(PAUSE);
+        profiler.exit();
+        profiler.exit();
+        profiler.finish();
+
+        let entry = profiler.state.borrow().entries["proc"];
+        assert_eq!(entry.hits, 2);
+        assert!(entry.time >= 2 * PAUSE);
+        assert!(entry.time < 3 * PAUSE);
+    }
+
+    #[test]
+    fn sibling_runs_are_all_timed() {
+        let profiler = Profiler::new(true);
+
+        profiler.enter("print");
+        for _ in 0..2 {
+            profiler.enter("call");
+            std::thread::sleep(PAUSE);
+            profiler.exit();
+        }
+        profiler.exit();
+        profiler.finish();
+
+        assert!(time(&profiler, "call") >= 2 * PAUSE);
+        assert!(time(&profiler, "print") >= time(&profiler, "call"));
+    }
+
+    #[test]
+    fn total_includes_time_outside_folders() {
+        let profiler = Profiler::new(true);
+        std::thread::sleep(PAUSE);
+
+        profiler.enter("print");
+        profiler.exit();
+        std::thread::sleep(PAUSE);
+        profiler.finish();
+
+        assert!(profiler.total() >= 2 * PAUSE);
+        assert!(profiler
+            .report("program", (0, Duration::ZERO))
+            .starts_with("profile of program : "));
+    }
+
+    #[test]
+    fn iterations_are_counted() {
+        let profiler = Profiler::new(true);
+
+        profiler.enter("while");
+        for _ in 0..3 {
+            profiler.iterate("while");
+        }
+        profiler.exit();
+        profiler.finish();
+
+        let report = profiler.report("program", (0, Duration::ZERO));
+        let loops = report.split("hottest loops :\n").nth(1).unwrap();
+        assert!(loops.lines().nth(1).unwrap().trim_start().starts_with("3 "));
+    }
+
+    #[test]
+    fn folded_stacks_join_folders() {
+        let profiler = Profiler::new(true);
+
+        profiler.enter("a");
+        profiler.enter("b");
+        std::thread::sleep(PAUSE);
+        profiler.exit();
+        profiler.exit();
+        profiler.finish();
+
+        let folded = profiler.folded_stacks();
+        let stacks: Vec<&str> = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert_eq!(stacks, ["a", "a;b"]);
+
+        let micros: u128 = folded
+            .lines()
+            .last()
+            .unwrap()
+            .rsplit_once(' ')
+            .unwrap()
+            .1
+            .parse()
+            .unwrap();
+        assert!(micros >= PAUSE.as_micros());
+    }
+
+    #[test]
+    fn disabled_profiler_records_nothing() {
+        let profiler = Profiler::new(false);
+
+        profiler.enter("a");
+        profiler.exit();
+
+        assert!(profiler.entries().is_empty());
+        assert_eq!(profiler.folded_stacks(), "");
+    }
+}
@@ -18,28 +18,42 @@ impl Scope {
         }
     }
 
-    /// Declare a new variable in this scope
-    ///
-    /// Params :
-    /// - folders_count : variable type is indentified with this parameter
-    /// - index : index of the variable in this scope. Variable will bear the name 'var_{index}'
-    pub fn declare_variable(&mut self, folders_count: usize, index: usize) -> std::io::Result<()> {
-        if self.variables.get(&index).is_some() {
-            return Err(input_error(format!("var_{index} declared more than once")));
-        }
+    /// Scope holding `variables`, restored from a snapshot
+    pub fn with_variables(
+        parent: Option<Rc<RefCell<Scope>>>,
+        variables: HashMap<usize, Variable>,
+    ) -> Self {
+        Scope { variables, parent }
+    }
 
-        let variable = Variable::new(folders_count)?;
-        self.variables.insert(index, variable);
+    pub fn parent(&self) -> Option<Rc<RefCell<Scope>>> {
+        self.parent.clone()
+    }
 
-        Ok(())
+    /// Variables declared in this scope, not in its parents
+    pub fn variables(&self) -> &HashMap<usize, Variable> {
+        &self.variables
     }
 
+    /// Outermost scope of the chain `scope` belongs to
+    pub fn global(scope: &Rc<RefCell<Scope>>) -> Rc<RefCell<Scope>> {
+        match &scope.borrow().parent {
+            Some(parent) => Scope::global(parent),
+            None => scope.clone(),
+        }
+    }
+
+    /// Declare a new variable in this scope
+    ///
+    /// Params :
+    /// - var_type : type of the variable, its value is null until set
+    /// - index : index of the variable in this scope. Variable will bear the name 'var_{index}'
     pub fn declare_variable_with_type(
         &mut self,
         var_type: VariableType,
         index: usize,
     ) -> std::io::Result<()> {
-        if self.variables.get(&index).is_some() {
+        if self.variables.contains_key(&index) {
             return Err(input_error(format!("var_{index} declared more than once")));
         }
 
@@ -0,0 +1,333 @@
+//! Interpreter state saved to a JSON file, to resume a program later.
+//!
+//! Folders are stored relative to the program folder. Procedures called from
+//! expressions are run by a nested interpreter, snapshots are only taken
+//! between two commands of the outermost one.
+
+use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    context::Context,
+    interpreter::{Frame, Interpreter},
+    items::{
+        procedure::Procedure,
+        variable::{Type, Variable},
+    },
+    scope::Scope,
+    utils::input_error,
+};
+
+/// format of the snapshot files, bumped on incompatible changes
+const VERSION: u64 = 1;
+
+pub struct Snapshot {
+    /// canonical folder of the program
+    pub program: String,
+
+    /// lines given before running that were not read yet, None when reading from stdin
+    pub input: Option<Vec<String>>,
+
+    /// every field, the program and the input included
+    state: Value,
+}
+
+impl Snapshot {
+    /// State of `interpreter` running the program of `context`
+    pub fn capture(interpreter: &Interpreter, context: &Context) -> std::io::Result<Self> {
+        let root = Path::new(&context.root);
+
+        // scopes are shared by frames, parents are listed before their children
+        let mut scopes: Vec<Rc<RefCell<Scope>>> = vec![];
+        let frames = interpreter
+            .frames
+            .iter()
+            .map(|frame| {
+                Ok(json!({
+                    "block": relative(root, &frame.block)?,
+                    "next": frame.next,
+                    "command": frame.command.as_deref().map(|folder| relative(root, folder)).transpose()?,
+                    "call": frame.call,
+                    "scope": scope_id(&frame.scope, &mut scopes),
+                }))
+            })
+            .collect::<std::io::Result<Vec<Value>>>()?;
+
+        let scopes: Vec<Value> = scopes
+            .iter()
+            .map(|scope| {
+                let scope = scope.borrow();
+                let variables: Map<String, Value> = scope
+                    .variables()
+                    .iter()
+                    .map(|(index, value)| (index.to_string(), variable_to_json(value)))
+                    .collect();
+
+                let parent = scope
+                    .parent()
+                    .and_then(|parent| scopes.iter().position(|known| Rc::ptr_eq(known, &parent)));
+
+                json!({
+                    "parent": parent,
+                    "variables": variables,
+                })
+            })
+            .collect();
+
+        let procedures: Map<String, Value> = context
+            .procedures
+            .borrow()
+            .iter()
+            .map(|(index, procedure)| {
+                Ok((
+                    index.to_string(),
+                    json!({
+                        "folder": relative(root, &procedure.folder)?,
+                        "body": relative(root, &procedure.body)?,
+                        "parameters": procedure.parameters,
+                    }),
+                ))
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        let input = context.input.pending();
+        let program = canonical(&context.root);
+
+        Ok(Snapshot {
+            state: json!({
+                "version": VERSION,
+                "program": program,
+                "input": input,
+                "procedures": procedures,
+                "scopes": scopes,
+                "frames": frames,
+            }),
+            program,
+            input,
+        })
+    }
+
+    /// The snapshot was taken while running the program in `folder`
+    pub fn is_taken_from(&self, folder: &str) -> bool {
+        canonical(&self.program) == canonical(folder)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, format!("{:#}\n", self.state))
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let invalid = || input_error(format!("{} : invalid snapshot", path.display()));
+
+        let state: Value = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|err| input_error(format!("{} : {err}", path.display())))?;
+
+        if state["version"].as_u64() != Some(VERSION) {
+            return Err(input_error(format!(
+                "{} : unsupported snapshot version, expected {VERSION}",
+                path.display()
+            )));
+        }
+
+        let program = state["program"].as_str().ok_or_else(invalid)?.to_owned();
+        let input = match &state["input"] {
+            Value::Null => None,
+            lines => Some(
+                lines
+                    .as_array()
+                    .ok_or_else(invalid)?
+                    .iter()
+                    .map(|line| line.as_str().map(String::from).ok_or_else(invalid))
+                    .collect::<std::io::Result<_>>()?,
+            ),
+        };
+
+        Ok(Snapshot {
+            program,
+            input,
+            state,
+        })
+    }
+
+    /// Interpreter continuing from this snapshot, procedures are registered in `context`
+    pub fn restore(&self, context: &Rc<Context>) -> std::io::Result<Interpreter> {
+        let invalid = || input_error(format!("{} : invalid snapshot", self.program));
+        let root = Path::new(&context.root);
+        let folder = |value: &Value| -> std::io::Result<String> {
+            match value.as_str().ok_or_else(invalid)? {
+                "" => Ok(context.root.clone()),
+                path => Ok(root.join(path).to_string_lossy().into_owned()),
+            }
+        };
+        let index = |value: &Value| {
+            value
+                .as_u64()
+                .map(|index| index as usize)
+                .ok_or_else(invalid)
+        };
+
+        for (name, procedure) in self.state["procedures"].as_object().ok_or_else(invalid)? {
+            context.procedures.borrow_mut().insert(
+                name.parse().map_err(|_| invalid())?,
+                Procedure {
+                    folder: folder(&procedure["folder"])?,
+                    body: folder(&procedure["body"])?,
+                    parameters: index(&procedure["parameters"])?,
+                },
+            );
+        }
+
+        let mut scopes: Vec<Rc<RefCell<Scope>>> = vec![];
+        for scope in self.state["scopes"].as_array().ok_or_else(invalid)? {
+            let parent = match &scope["parent"] {
+                Value::Null => None,
+                parent => Some(scopes.get(index(parent)?).ok_or_else(invalid)?.clone()),
+            };
+
+            let mut variables = HashMap::new();
+            for (name, value) in scope["variables"].as_object().ok_or_else(invalid)? {
+                variables.insert(
+                    name.parse().map_err(|_| invalid())?,
+                    variable_from_json(value).ok_or_else(invalid)?,
+                );
+            }
+
+            scopes.push(Rc::new(RefCell::new(Scope::with_variables(
+                parent, variables,
+            ))));
+        }
+
+        let mut frames = vec![];
+        for frame in self.state["frames"].as_array().ok_or_else(invalid)? {
+            let block = folder(&frame["block"])?;
+            let commands = context.cache.sorted_subfolders(&block)?;
+            let next = index(&frame["next"])?;
+            if next > commands.len() {
+                return Err(input_error(format!(
+                    "{block} : the program changed since the snapshot was taken"
+                )));
+            }
+
+            let call = frame["call"].as_bool().ok_or_else(invalid)?;
+            if call {
+                context.calls.borrow_mut().push(None);
+            }
+
+            frames.push(Frame {
+                block,
+                commands,
+                next,
+                command: match &frame["command"] {
+                    Value::Null => None,
+                    command => Some(folder(command)?),
+                },
+                call,
+                scope: scopes
+                    .get(index(&frame["scope"])?)
+                    .ok_or_else(invalid)?
+                    .clone(),
+            });
+        }
+
+        Ok(Interpreter::with_frames(frames, context))
+    }
+}
+
+/// Absolute path of `folder` without symbolic links, as it is when it does not exist
+fn canonical(folder: &str) -> String {
+    std::fs::canonicalize(folder)
+        .ok()
+        .and_then(|path| path.to_str().map(str::to_owned))
+        .unwrap_or_else(|| folder.to_owned())
+}
+
+/// `folder` relative to the program folder
+fn relative(root: &Path, folder: &str) -> std::io::Result<String> {
+    Path::new(folder)
+        .strip_prefix(root)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|_| input_error(format!("{folder} : folder outside of the program")))
+}
+
+/// Index of `scope` in `scopes`, adding it after its parents if it is not listed yet
+fn scope_id(scope: &Rc<RefCell<Scope>>, scopes: &mut Vec<Rc<RefCell<Scope>>>) -> usize {
+    if let Some(id) = scopes.iter().position(|known| Rc::ptr_eq(known, scope)) {
+        return id;
+    }
+
+    if let Some(parent) = scope.borrow().parent() {
+        scope_id(&parent, scopes);
+    }
+
+    scopes.push(scope.clone());
+    scopes.len() - 1
+}
+
+/// Every type a variable can have, arrays included
+fn types() -> Vec<Type> {
+    let scalars = [Type::Int, Type::Float, Type::String, Type::Char, Type::Bool];
+    scalars
+        .into_iter()
+        .chain(scalars.into_iter().filter_map(Type::array_of))
+        .collect()
+}
+
+fn variable_to_json(value: &Variable) -> Value {
+    json!({
+        "type": value.get_type().as_str(),
+        "value": value_to_json(value),
+    })
+}
+
+/// Floats are stored as strings, to keep NaN and infinities
+fn value_to_json(value: &Variable) -> Value {
+    match value {
+        Variable::Int(Some(value)) => json!(value),
+        Variable::Float(Some(value)) => json!(value.to_string()),
+        Variable::String(Some(value)) => json!(value),
+        Variable::Char(Some(value)) => json!(value.to_string()),
+        Variable::Bool(Some(value)) => json!(value),
+        Variable::Array(_, Some(values)) => values.iter().map(value_to_json).collect(),
+        _ => Value::Null,
+    }
+}
+
+fn variable_from_json(variable: &Value) -> Option<Variable> {
+    let name = variable["type"].as_str()?;
+    let var_type = types().into_iter().find(|t| t.as_str() == name)?;
+
+    value_from_json(var_type, &variable["value"])
+}
+
+fn value_from_json(var_type: Type, value: &Value) -> Option<Variable> {
+    if value.is_null() {
+        return Some(Variable::from(var_type));
+    }
+
+    let variable = match var_type {
+        Type::Int => Variable::Int(Some(i32::try_from(value.as_i64()?).ok()?)),
+        Type::Float => Variable::Float(Some(value.as_str()?.parse().ok()?)),
+        Type::String => Variable::String(Some(value.as_str()?.to_owned())),
+        Type::Char => {
+            let mut chars = value.as_str()?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(value), None) => Variable::Char(Some(value)),
+                _ => return None,
+            }
+        }
+        Type::Bool => Variable::Bool(Some(value.as_bool()?)),
+        Type::Array(element) => Variable::Array(
+            *element,
+            Some(
+                value
+                    .as_array()?
+                    .iter()
+                    .map(|value| value_from_json(*element, value))
+                    .collect::<Option<_>>()?,
+            ),
+        ),
+    };
+
+    Some(variable)
+}
@@ -1,3 +0,0 @@
-pub trait Transpile {
-    fn transpile(&mut self) -> std::io::Result<String>;
-}
\ No newline at end of file
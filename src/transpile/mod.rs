@@ -0,0 +1,17 @@
+use crate::analysis::ScopeAnalysis;
+
+pub mod rust;
+pub mod wat;
+
+/// Language emitted by the transpiler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Target {
+    Rust,
+
+    /// WebAssembly text format
+    Wat,
+}
+
+pub trait Transpile {
+    fn transpile(&self, analysis: &ScopeAnalysis) -> std::io::Result<String>;
+}
@@ -0,0 +1,69 @@
+//! Rust backend, commands are transpiled by their `Transpile` implementation
+//! and called procedures become functions following `main`.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    analysis::ScopeAnalysis, context::Context, items::command::Command, scope::Scope,
+    transpile::Transpile, utils::input_error,
+};
+
+/// Source of a Rust program running the program in `folder`
+pub fn transpile(
+    folder: &str,
+    analysis: &ScopeAnalysis,
+    context: &Rc<Context>,
+) -> std::io::Result<String> {
+    // procedures become functions, which do not see the variables of 'main'
+    if let Some((folder, index)) = analysis.global_use() {
+        return Err(input_error(format!(
+            "{folder} : procedures can not use global variable var_{index} once transpiled"
+        )));
+    }
+
+    let global_scope = Rc::new(RefCell::new(Scope::new(None)));
+
+    let mut lines: Vec<String> = vec![];
+    let mut using_print = false;
+
+    for folder in context.cache.sorted_subfolders(folder)? {
+        let cmd = Command::new(&folder, &global_scope, context)?;
+        if context.optimizations.is_dead(cmd.folder()) {
+            continue;
+        }
+
+        lines.extend(
+            cmd.transpile(analysis)?
+                .split('\n')
+                .map(|line| line.to_owned()),
+        );
+
+        if lines.iter().any(|line| line.contains("print")) {
+            using_print = true;
+        }
+    }
+
+    let mut functions = vec![];
+    for folder in analysis.called_procedures() {
+        let cmd = Command::new(folder, &global_scope, context)?;
+        if let Some(function) = cmd.transpile_procedure(analysis)? {
+            using_print |= function.contains("print");
+            functions.push(function);
+        }
+    }
+
+    let mut program = vec![];
+    if using_print {
+        program.push("use std::io::Write;\n".to_owned());
+    }
+
+    program.push("fn main() {".to_owned());
+    program.extend(lines.iter().map(|line| format!("\t{line}")));
+    program.push("}".to_owned());
+
+    for function in functions {
+        program.push(format!("\n{function}"));
+    }
+
+    Ok(program.join("\n"))
+}
@@ -0,0 +1,980 @@
+//! WebAssembly text backend.
+//!
+//! Ints map to `i32`, Floats to `f32` and Chars to their code point as `i32`.
+//! Strings are pointers into the exported linear memory, laid out as a
+//! little-endian `i32` byte length followed by the bytes. Literal strings live
+//! in a data segment, computed ones are taken from a bump allocator.
+//!
+//! The module exports `main` and `memory` and imports its I/O from `env` :
+//! - `print_i32 (i32)`, `print_f32 (f32)`, `print_char (i32)`, `print_str (i32)`
+//! - `input_i32 -> i32`, `input_f32 -> f32`, `input_char -> i32`
+//! - `input_str (buffer: i32, capacity: i32) -> i32`, returns the length written
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    analysis::ScopeAnalysis,
+    context::Context,
+    items::{
+        command::{Command, CommandType},
+        expression::{Expression, ExpressionType},
+        variable::{Type, Variable},
+    },
+    numeric::NumericMode,
+    scope::Scope,
+    utils::{input_error, sorted_subfolders},
+};
+
+/// capacity of the buffer handed to `input_str`
+const INPUT_CAPACITY: usize = 1024;
+
+const IMPORTS: &str = r#"  (import "env" "print_i32" (func $print_i32 (param i32)))
+  (import "env" "print_f32" (func $print_f32 (param f32)))
+  (import "env" "print_char" (func $print_char (param i32)))
+  (import "env" "print_str" (func $print_str (param i32)))
+  (import "env" "input_i32" (func $input_i32 (result i32)))
+  (import "env" "input_f32" (func $input_f32 (result f32)))
+  (import "env" "input_char" (func $input_char (result i32)))
+  (import "env" "input_str" (func $input_str (param i32 i32) (result i32)))"#;
+
+const RUNTIME: &str = r#"  (func $alloc (param $size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.set $ptr
+    global.get $heap
+    local.get $size
+    i32.add
+    i32.const 3
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    (block $done
+      (loop $grow
+        global.get $heap
+        memory.size
+        i32.const 16
+        i32.shl
+        i32.le_u
+        br_if $done
+        i32.const 1
+        memory.grow
+        drop
+        br $grow))
+    local.get $ptr)
+
+  (func $copy (param $dst i32) (param $src i32) (param $len i32)
+    (block $done
+      (loop $next
+        local.get $len
+        i32.eqz
+        br_if $done
+        local.get $dst
+        local.get $src
+        i32.load8_u
+        i32.store8
+        local.get $dst
+        i32.const 1
+        i32.add
+        local.set $dst
+        local.get $src
+        i32.const 1
+        i32.add
+        local.set $src
+        local.get $len
+        i32.const 1
+        i32.sub
+        local.set $len
+        br $next)))
+
+  (func $concat (param $a i32) (param $b i32) (result i32)
+    (local $len_a i32)
+    (local $len_b i32)
+    (local $ptr i32)
+    local.get $a
+    i32.load
+    local.set $len_a
+    local.get $b
+    i32.load
+    local.set $len_b
+    local.get $len_a
+    local.get $len_b
+    i32.add
+    i32.const 4
+    i32.add
+    call $alloc
+    local.set $ptr
+    local.get $ptr
+    local.get $len_a
+    local.get $len_b
+    i32.add
+    i32.store
+    local.get $ptr
+    i32.const 4
+    i32.add
+    local.get $a
+    i32.const 4
+    i32.add
+    local.get $len_a
+    call $copy
+    local.get $ptr
+    i32.const 4
+    i32.add
+    local.get $len_a
+    i32.add
+    local.get $b
+    i32.const 4
+    i32.add
+    local.get $len_b
+    call $copy
+    local.get $ptr)
+
+  (func $str_cmp (param $a i32) (param $b i32) (result i32)
+    (local $len_a i32)
+    (local $len_b i32)
+    (local $i i32)
+    (local $x i32)
+    (local $y i32)
+    local.get $a
+    i32.load
+    local.set $len_a
+    local.get $b
+    i32.load
+    local.set $len_b
+    (block $done
+      (loop $next
+        local.get $i
+        local.get $len_a
+        i32.ge_u
+        local.get $i
+        local.get $len_b
+        i32.ge_u
+        i32.or
+        br_if $done
+        local.get $a
+        local.get $i
+        i32.add
+        i32.load8_u offset=4
+        local.set $x
+        local.get $b
+        local.get $i
+        i32.add
+        i32.load8_u offset=4
+        local.set $y
+        local.get $x
+        local.get $y
+        i32.ne
+        (if
+          (then
+            i32.const 1
+            i32.const -1
+            local.get $x
+            local.get $y
+            i32.gt_u
+            select
+            return))
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $next))
+    local.get $len_a
+    local.get $len_b
+    i32.gt_u
+    local.get $len_a
+    local.get $len_b
+    i32.lt_u
+    i32.sub)
+
+  (func $read_str (result i32)
+    (local $ptr i32)
+    (local $len i32)
+    i32.const INPUT_SIZE
+    call $alloc
+    local.set $ptr
+    local.get $ptr
+    i32.const 4
+    i32.add
+    i32.const INPUT_CAPACITY
+    call $input_str
+    local.set $len
+    local.get $ptr
+    local.get $len
+    i32.store
+    ;; hand unused capacity back to the allocator
+    local.get $ptr
+    local.get $len
+    i32.add
+    i32.const 7
+    i32.add
+    i32.const -4
+    i32.and
+    global.set $heap
+    local.get $ptr)
+
+  ;; byte at an index, traps when out of bounds
+  (func $str_at (param $s i32) (param $i i32) (result i32)
+    local.get $i
+    local.get $s
+    i32.load
+    i32.ge_u
+    (if
+      (then
+        unreachable))
+    local.get $s
+    local.get $i
+    i32.add
+    i32.load8_u offset=4)
+
+  ;; bytes from start to end excluded, traps when out of bounds
+  (func $substr (param $s i32) (param $start i32) (param $end i32) (result i32)
+    (local $ptr i32)
+    local.get $start
+    local.get $end
+    i32.gt_u
+    local.get $end
+    local.get $s
+    i32.load
+    i32.gt_u
+    i32.or
+    (if
+      (then
+        unreachable))
+    local.get $end
+    local.get $start
+    i32.sub
+    i32.const 4
+    i32.add
+    call $alloc
+    local.set $ptr
+    local.get $ptr
+    local.get $end
+    local.get $start
+    i32.sub
+    i32.store
+    local.get $ptr
+    i32.const 4
+    i32.add
+    local.get $s
+    i32.const 4
+    i32.add
+    local.get $start
+    i32.add
+    local.get $end
+    local.get $start
+    i32.sub
+    call $copy
+    local.get $ptr)
+
+  ;; string made of a single char
+  (func $char_str (param $c i32) (result i32)
+    (local $ptr i32)
+    i32.const 5
+    call $alloc
+    local.set $ptr
+    local.get $ptr
+    i32.const 1
+    i32.store
+    local.get $ptr
+    local.get $c
+    i32.store8 offset=4
+    local.get $ptr)
+
+  ;; truncated Int division, i32.div_s traps on -2147483648 / -1 instead of wrapping
+  (func $wrapping_div (param $a i32) (param $b i32) (result i32)
+    local.get $b
+    i32.const -1
+    i32.eq
+    if (result i32)
+      i32.const 0
+      local.get $a
+      i32.sub
+    else
+      local.get $a
+      local.get $b
+      i32.div_s
+    end)
+
+  ;; truncated remainder, same sign as the dividend
+  (func $f32_rem (param $a f32) (param $b f32) (result f32)
+    local.get $a
+    local.get $a
+    local.get $b
+    f32.div
+    f32.trunc
+    local.get $b
+    f32.mul
+    f32.sub)"#;
+
+/// Int arithmetic of the checked and saturating modes, computed on i64 then
+/// narrowed by `$int_result`
+const INT_ARITHMETIC: &str = r#"  (func $int_add (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    i64.extend_i32_s
+    local.get $b
+    i64.extend_i32_s
+    i64.add
+    call $int_result)
+
+  (func $int_sub (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    i64.extend_i32_s
+    local.get $b
+    i64.extend_i32_s
+    i64.sub
+    call $int_result)
+
+  (func $int_mul (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    i64.extend_i32_s
+    local.get $b
+    i64.extend_i32_s
+    i64.mul
+    call $int_result)
+
+  (func $int_div (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    i64.extend_i32_s
+    local.get $b
+    i64.extend_i32_s
+    i64.div_s
+    call $int_result)"#;
+
+const CHECKED_RUNTIME: &str = r#"  ;; traps when the value does not fit in an i32
+  (func $int_result (param $value i64) (result i32)
+    local.get $value
+    local.get $value
+    i32.wrap_i64
+    i64.extend_i32_s
+    i64.ne
+    if
+      unreachable
+    end
+    local.get $value
+    i32.wrap_i64)
+
+  ;; divisions by zero trap
+  (func $checked_div (param $a f32) (param $b f32) (result f32)
+    local.get $b
+    f32.const 0
+    f32.eq
+    if
+      unreachable
+    end
+    local.get $a
+    local.get $b
+    f32.div)
+
+  (func $checked_rem (param $a f32) (param $b f32) (result f32)
+    local.get $b
+    f32.const 0
+    f32.eq
+    if
+      unreachable
+    end
+    local.get $a
+    local.get $b
+    call $f32_rem)"#;
+
+const SATURATING_RUNTIME: &str = r#"  ;; clamps the value to the i32 range
+  (func $int_result (param $value i64) (result i32)
+    local.get $value
+    i64.const -2147483648
+    local.get $value
+    i64.const -2147483648
+    i64.gt_s
+    select
+    local.tee $value
+    i64.const 2147483647
+    local.get $value
+    i64.const 2147483647
+    i64.lt_s
+    select
+    i32.wrap_i64)"#;
+
+fn value_type(var_type: Type) -> &'static str {
+    match var_type {
+        Type::Float => "f32",
+        Type::Int | Type::Char | Type::String | Type::Bool => "i32",
+        Type::Array(_) => unreachable!("arrays are rejected at their declaration"),
+    }
+}
+
+/// Emit a WebAssembly text module for the program in `folder`
+pub fn transpile(
+    folder: &str,
+    analysis: &ScopeAnalysis,
+    context: &Rc<Context>,
+) -> std::io::Result<String> {
+    let mut emitter = WatEmitter::new(analysis, context);
+
+    // the static string used as default value of declared strings
+    emitter.empty_string = emitter.intern(&[]);
+
+    let mut body = vec![];
+    emitter.block(folder, &mut body, 2)?;
+
+    let mut module = vec!["(module".to_owned(), IMPORTS.to_owned()];
+
+    module.push(String::new());
+    module.push("  (memory (export \"memory\") 1)".to_owned());
+    module.push(format!(
+        "  (global $heap (mut i32) (i32.const {}))",
+        emitter.data.len()
+    ));
+    module.push(format!(
+        "  (data (i32.const 0) \"{}\")",
+        emitter
+            .data
+            .iter()
+            .map(|byte| format!("\\{byte:02x}"))
+            .collect::<String>()
+    ));
+
+    module.push(String::new());
+    module.push(
+        RUNTIME
+            .replace("INPUT_SIZE", &(INPUT_CAPACITY + 4).to_string())
+            .replace("INPUT_CAPACITY", &INPUT_CAPACITY.to_string()),
+    );
+
+    let numeric_runtime = match context.numeric {
+        NumericMode::Wrapping => None,
+        NumericMode::Checked => Some(CHECKED_RUNTIME),
+        NumericMode::Saturating => Some(SATURATING_RUNTIME),
+    };
+    if let Some(numeric_runtime) = numeric_runtime {
+        module.push(String::new());
+        module.push(INT_ARITHMETIC.to_owned());
+        module.push(String::new());
+        module.push(numeric_runtime.to_owned());
+    }
+
+    module.push(String::new());
+    module.push("  (func (export \"main\")".to_owned());
+    for (name, var_type) in &emitter.locals {
+        module.push(format!("    (local ${name} {})", value_type(*var_type)));
+    }
+    module.extend(body);
+    module.push("  )".to_owned());
+    module.push(")".to_owned());
+
+    Ok(module.join("\n"))
+}
+
+struct WatEmitter<'a> {
+    analysis: &'a ScopeAnalysis,
+
+    /// every local of the main function, one per declaration site
+    locals: Vec<(String, Type)>,
+
+    /// visible variables, innermost scope last
+    scopes: Vec<HashMap<usize, String>>,
+
+    /// content of the data segment
+    data: Vec<u8>,
+
+    /// address of the interned literal strings
+    strings: HashMap<Vec<u8>, usize>,
+
+    empty_string: usize,
+
+    /// used to name loop labels
+    label_count: usize,
+
+    /// scope handed to commands and expressions, never filled
+    scope: Rc<RefCell<Scope>>,
+
+    context: Rc<Context>,
+}
+
+impl<'a> WatEmitter<'a> {
+    fn new(analysis: &'a ScopeAnalysis, context: &Rc<Context>) -> Self {
+        WatEmitter {
+            analysis,
+            locals: vec![],
+            scopes: vec![HashMap::new()],
+            data: vec![],
+            strings: HashMap::new(),
+            empty_string: 0,
+            label_count: 0,
+            scope: Rc::new(RefCell::new(Scope::new(None))),
+            context: context.clone(),
+        }
+    }
+
+    /// Store a length-prefixed string in the data segment
+    fn intern(&mut self, bytes: &[u8]) -> usize {
+        if let Some(address) = self.strings.get(bytes) {
+            return *address;
+        }
+
+        let address = self.data.len();
+        self.data
+            .extend_from_slice(&(bytes.len() as i32).to_le_bytes());
+        self.data.extend_from_slice(bytes);
+        while !self.data.len().is_multiple_of(4) {
+            self.data.push(0);
+        }
+
+        self.strings.insert(bytes.to_vec(), address);
+        address
+    }
+
+    /// Instruction pushing a constant value
+    fn constant(&mut self, value: Variable) -> String {
+        match value {
+            Variable::Int(value) => format!("i32.const {}", value.unwrap()),
+            Variable::Char(value) => format!("i32.const {}", value.unwrap() as u32),
+            Variable::Bool(value) => format!("i32.const {}", value.unwrap() as i32),
+            Variable::Float(value) => {
+                let value = value.unwrap();
+                if value.is_nan() {
+                    "f32.const nan".to_owned()
+                } else if value.is_infinite() {
+                    format!("f32.const {}inf", if value < 0.0 { "-" } else { "" })
+                } else {
+                    format!("f32.const {value:?}")
+                }
+            }
+            Variable::String(value) => {
+                let bytes: Vec<u8> = value.unwrap().chars().map(|c| c as u8).collect();
+                format!("i32.const {}", self.intern(&bytes))
+            }
+            Variable::Array(..) => unreachable!("arrays are never folded"),
+        }
+    }
+
+    fn declare_local(&mut self, index: usize, var_type: Type) -> String {
+        let name = format!("var_{index}_{}", self.locals.len());
+        self.locals.push((name.clone(), var_type));
+        self.scopes.last_mut().unwrap().insert(index, name.clone());
+
+        name
+    }
+
+    fn resolve_local(&self, index: usize) -> std::io::Result<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&index).cloned())
+            .ok_or_else(|| input_error(format!("Use of undeclared variable var_{index}")))
+    }
+
+    fn block(&mut self, folder: &str, out: &mut Vec<String>, depth: usize) -> std::io::Result<()> {
+        for folder in sorted_subfolders(folder)? {
+            let cmd = Command::new(&folder, &self.scope, &self.context)?;
+            if self.context.optimizations.is_dead(cmd.folder()) {
+                continue;
+            }
+
+            self.command(&cmd, out, depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn scoped_block(
+        &mut self,
+        folder: &str,
+        out: &mut Vec<String>,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        self.scopes.push(HashMap::new());
+        let result = self.block(folder, out, depth);
+        self.scopes.pop();
+
+        result
+    }
+
+    fn command(
+        &mut self,
+        cmd: &Command,
+        out: &mut Vec<String>,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+
+        match cmd.command_type {
+            CommandType::Declare => {
+                let binding = self.analysis.binding(cmd.folder())?;
+                if let Type::Array(_) = binding.var_type {
+                    return Err(input_error(format!(
+                        "{} : wat target does not support arrays",
+                        cmd.folder()
+                    )));
+                }
+                let local = self.declare_local(binding.index, binding.var_type);
+
+                let default = match binding.var_type {
+                    Type::Float => "f32.const 0".to_owned(),
+                    Type::String => format!("i32.const {}", self.empty_string),
+                    Type::Int | Type::Char | Type::Bool => "i32.const 0".to_owned(),
+                    Type::Array(_) => unreachable!(),
+                };
+                out.push(format!("{indent}{default}"));
+                out.push(format!("{indent}local.set ${local}"));
+            }
+
+            CommandType::Let => {
+                let binding = self.analysis.binding(cmd.folder())?;
+                self.expression(&cmd.expression()?, out, depth)?;
+
+                let local = if binding.declares {
+                    self.declare_local(binding.index, binding.var_type)
+                } else {
+                    self.resolve_local(binding.index)?
+                };
+                out.push(format!("{indent}local.set ${local}"));
+            }
+
+            CommandType::Print => {
+                let exp = cmd.expression()?;
+                let exp_type = self.analysis.type_of(exp.folder())?;
+
+                // booleans are printed as the strings 'true' or 'false'
+                if exp_type == Type::Bool {
+                    let (yes, no) = (self.intern(b"true"), self.intern(b"false"));
+                    out.push(format!("{indent}i32.const {yes}"));
+                    out.push(format!("{indent}i32.const {no}"));
+                }
+                self.expression(&exp, out, depth)?;
+
+                let function = match exp_type {
+                    Type::Bool => {
+                        out.push(format!("{indent}select"));
+                        "print_str"
+                    }
+                    Type::Int => "print_i32",
+                    Type::Float => "print_f32",
+                    Type::Char => "print_char",
+                    Type::String => "print_str",
+                    Type::Array(_) => unreachable!("arrays are rejected at their declaration"),
+                };
+                out.push(format!("{indent}call ${function}"));
+            }
+
+            CommandType::Input => {
+                let binding = self.analysis.binding(cmd.folder())?;
+
+                let function = match binding.var_type {
+                    Type::Int => "input_i32",
+                    Type::Float => "input_f32",
+                    Type::Char => "input_char",
+                    Type::String => "read_str",
+                    Type::Bool | Type::Array(_) => unreachable!("rejected by the analysis"),
+                };
+                out.push(format!("{indent}call ${function}"));
+
+                let local = if binding.declares {
+                    self.declare_local(binding.index, binding.var_type)
+                } else {
+                    self.resolve_local(binding.index)?
+                };
+                out.push(format!("{indent}local.set ${local}"));
+            }
+
+            CommandType::If
+                if self.context.optimizations.constant_condition(cmd.folder()) == Some(true) =>
+            {
+                self.scoped_block(cmd.body_folder()?, out, depth)?;
+            }
+
+            // dead 'if' commands without 'else' are skipped
+            CommandType::If
+                if self.context.optimizations.constant_condition(cmd.folder()) == Some(false) =>
+            {
+                if let Some(else_folder) = cmd.else_folder() {
+                    self.scoped_block(else_folder, out, depth)?;
+                }
+            }
+
+            CommandType::If => {
+                self.condition(&cmd.expression()?, out, depth)?;
+                out.push(format!("{indent}(if"));
+                out.push(format!("{indent}  (then"));
+                self.scoped_block(cmd.body_folder()?, out, depth + 2)?;
+
+                if let Some(else_folder) = cmd.else_folder() {
+                    out.push(format!("{indent}  )"));
+                    out.push(format!("{indent}  (else"));
+                    self.scoped_block(else_folder, out, depth + 2)?;
+                }
+                out.push(format!("{indent}  ))"));
+            }
+
+            CommandType::While => {
+                let label = self.label_count;
+                self.label_count += 1;
+
+                out.push(format!("{indent}(block $break_{label}"));
+                out.push(format!("{indent}  (loop $continue_{label}"));
+                self.condition(&cmd.expression()?, out, depth + 2)?;
+                out.push(format!("{indent}    i32.eqz"));
+                out.push(format!("{indent}    br_if $break_{label}"));
+                self.scoped_block(cmd.body_folder()?, out, depth + 2)?;
+                out.push(format!("{indent}    br $continue_{label}))"));
+            }
+
+            CommandType::Write | CommandType::CreateFolder | CommandType::RemoveFolder => {
+                return Err(input_error(format!(
+                    "{} : '{}' command modifies the program folders, it can not be transpiled",
+                    cmd.folder(),
+                    cmd.command_type.as_str()
+                )));
+            }
+
+            CommandType::Define | CommandType::Call | CommandType::Return => {
+                return Err(input_error(format!(
+                    "{} : wat target does not support procedures",
+                    cmd.folder()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Push the truthiness of an expression as an i32
+    fn condition(
+        &mut self,
+        exp: &Expression,
+        out: &mut Vec<String>,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+        self.expression(exp, out, depth)?;
+
+        match self.analysis.type_of(exp.folder())? {
+            Type::Float => {
+                out.push(format!("{indent}f32.const 0"));
+                out.push(format!("{indent}f32.ne"));
+            }
+            Type::String => {
+                // non empty string
+                out.push(format!("{indent}i32.load"));
+            }
+            Type::Int | Type::Char | Type::Bool => {}
+            Type::Array(_) => unreachable!("arrays are rejected at their declaration"),
+        }
+
+        Ok(())
+    }
+
+    fn expression(
+        &mut self,
+        exp: &Expression,
+        out: &mut Vec<String>,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+
+        match exp.expression_type {
+            ExpressionType::Variable => {
+                let local = self.resolve_local(exp.variable_index()?)?;
+                out.push(format!("{indent}local.get ${local}"));
+            }
+
+            _ if self.context.optimizations.folded(exp.folder()).is_some() => {
+                let value = self.context.optimizations.folded(exp.folder()).cloned();
+                let instruction = self.constant(value.unwrap());
+                out.push(format!("{indent}{instruction}"));
+            }
+
+            ExpressionType::LiteralValue => {
+                let instruction = self.constant(exp.get_literal_value()?);
+                out.push(format!("{indent}{instruction}"));
+            }
+
+            ExpressionType::Call => {
+                return Err(input_error(format!(
+                    "{} : wat target does not support procedures",
+                    exp.folder()
+                )));
+            }
+
+            exp_type if exp_type.is_sequence() => {
+                let operands = exp.sequence_operands()?;
+                if self.analysis.type_of(operands[0].folder())? != Type::String {
+                    return Err(input_error(format!(
+                        "{} : wat target does not support arrays",
+                        exp.folder()
+                    )));
+                }
+
+                for operand in &operands {
+                    self.expression(operand, out, depth)?;
+                }
+
+                let instruction = match exp_type {
+                    ExpressionType::Length => "i32.load",
+                    ExpressionType::IndexGet => "call $str_at",
+                    _ => "call $substr",
+                };
+                out.push(format!("{indent}{instruction}"));
+            }
+
+            ExpressionType::Cast => {
+                let operand = exp.cast_operand()?;
+                let from = self.analysis.type_of(operand.folder())?;
+                let to = exp.target_type()?;
+
+                let instructions: &[&str] = match (from, to) {
+                    (from, to) if from == to => &[],
+                    (Type::Int, Type::Float) => &["f32.convert_i32_s"],
+                    // traps on NaN and out of range values
+                    (Type::Float, Type::Int) => &["i32.trunc_f32_s"],
+                    (Type::Char | Type::Bool, Type::Int) => &[],
+                    (Type::Int | Type::Char, Type::Bool) => &["i32.eqz", "i32.eqz"],
+                    (Type::Float, Type::Bool) => &["f32.const 0", "f32.ne"],
+                    _ => {
+                        return Err(input_error(format!(
+                            "{} : wat target can not cast {from:?} to {to:?}",
+                            exp.folder()
+                        )))
+                    }
+                };
+
+                self.expression(&operand, out, depth)?;
+                for instruction in instructions {
+                    out.push(format!("{indent}{instruction}"));
+                }
+            }
+
+            ExpressionType::Not => {
+                self.condition(&exp.operand()?, out, depth)?;
+                out.push(format!("{indent}i32.eqz"));
+            }
+
+            ExpressionType::And | ExpressionType::Or => {
+                let (first, second) = exp.operands()?;
+
+                for operand in [first, second] {
+                    self.condition(&operand, out, depth)?;
+
+                    // any non zero value becomes 1
+                    out.push(format!("{indent}i32.eqz"));
+                    out.push(format!("{indent}i32.eqz"));
+                }
+
+                let instruction = match exp.expression_type {
+                    ExpressionType::And => "i32.and",
+                    _ => "i32.or",
+                };
+                out.push(format!("{indent}{instruction}"));
+            }
+
+            _ => {
+                let (first, second) = exp.operands()?;
+                let a = self.analysis.type_of(first.folder())?;
+                let b = self.analysis.type_of(second.folder())?;
+
+                let numeric = |t: Type| t == Type::Int || t == Type::Float;
+                let operator = exp.expression_type;
+
+                // operands are promoted to f32 as soon as one of them is a Float
+                let float = numeric(a)
+                    && numeric(b)
+                    && (a == Type::Float || b == Type::Float || operator == ExpressionType::Divide);
+
+                let unsupported = || {
+                    input_error(format!(
+                        "{} : wat target can not apply {operator:?} to {a:?} and {b:?}",
+                        exp.folder()
+                    ))
+                };
+
+                // chars are concatenated as strings of a single byte
+                let concat = operator == ExpressionType::Add
+                    && matches!(a, Type::String | Type::Char)
+                    && matches!(b, Type::String | Type::Char);
+
+                self.expression(&first, out, depth)?;
+                if float && a == Type::Int {
+                    out.push(format!("{indent}f32.convert_i32_s"));
+                }
+                if concat && a == Type::Char {
+                    out.push(format!("{indent}call $char_str"));
+                }
+
+                self.expression(&second, out, depth)?;
+                if float && b == Type::Int {
+                    out.push(format!("{indent}f32.convert_i32_s"));
+                }
+                if concat && b == Type::Char {
+                    out.push(format!("{indent}call $char_str"));
+                }
+
+                let checked = self.context.numeric == NumericMode::Checked;
+                let wrapping = self.context.numeric == NumericMode::Wrapping;
+
+                let instructions: &[&str] = if concat {
+                    &["call $concat"]
+                } else if a == Type::String && b == Type::String {
+                    match operator {
+                        ExpressionType::EqualTo => &["call $str_cmp", "i32.eqz"],
+                        ExpressionType::GreaterThan => {
+                            &["call $str_cmp", "i32.const 0", "i32.gt_s"]
+                        }
+                        ExpressionType::LessThan => &["call $str_cmp", "i32.const 0", "i32.lt_s"],
+                        ExpressionType::NotEqualTo => &["call $str_cmp", "i32.const 0", "i32.ne"],
+                        _ => return Err(unsupported()),
+                    }
+                } else if float {
+                    match operator {
+                        ExpressionType::Add => &["f32.add"],
+                        ExpressionType::Substract => &["f32.sub"],
+                        ExpressionType::Multiply => &["f32.mul"],
+                        ExpressionType::Divide if checked => &["call $checked_div"],
+                        ExpressionType::Divide => &["f32.div"],
+                        ExpressionType::EqualTo => &["f32.eq"],
+                        ExpressionType::GreaterThan => &["f32.gt"],
+                        ExpressionType::LessThan => &["f32.lt"],
+                        ExpressionType::Modulo if checked => &["call $checked_rem"],
+                        ExpressionType::Modulo => &["call $f32_rem"],
+                        ExpressionType::NotEqualTo => &["f32.ne"],
+                        _ => return Err(unsupported()),
+                    }
+                } else if a == Type::Int && b == Type::Int {
+                    match operator {
+                        ExpressionType::IntDivide if wrapping => &["call $wrapping_div"],
+                        ExpressionType::IntDivide => &["call $int_div"],
+                        ExpressionType::Add if !wrapping => &["call $int_add"],
+                        ExpressionType::Substract if !wrapping => &["call $int_sub"],
+                        ExpressionType::Multiply if !wrapping => &["call $int_mul"],
+                        ExpressionType::Add => &["i32.add"],
+                        ExpressionType::Substract => &["i32.sub"],
+                        ExpressionType::Multiply => &["i32.mul"],
+                        ExpressionType::EqualTo => &["i32.eq"],
+                        ExpressionType::GreaterThan => &["i32.gt_s"],
+                        ExpressionType::LessThan => &["i32.lt_s"],
+                        ExpressionType::Modulo => &["i32.rem_s"],
+                        ExpressionType::NotEqualTo => &["i32.ne"],
+                        ExpressionType::BitAnd => &["i32.and"],
+                        ExpressionType::BitOr => &["i32.or"],
+                        ExpressionType::BitXor => &["i32.xor"],
+                        ExpressionType::ShiftLeft => &["i32.shl"],
+                        ExpressionType::ShiftRight => &["i32.shr_s"],
+                        _ => return Err(unsupported()),
+                    }
+                } else if a == b && (a == Type::Char || a == Type::Bool) {
+                    match operator {
+                        ExpressionType::EqualTo => &["i32.eq"],
+                        ExpressionType::GreaterThan => &["i32.gt_u"],
+                        ExpressionType::LessThan => &["i32.lt_u"],
+                        ExpressionType::NotEqualTo => &["i32.ne"],
+                        _ => return Err(unsupported()),
+                    }
+                } else {
+                    return Err(unsupported());
+                };
+
+                for instruction in instructions {
+                    out.push(format!("{indent}{instruction}"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
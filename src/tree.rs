@@ -0,0 +1,116 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{items::variable::Variable, utils::input_error};
+
+/// Resolve `path`, relative to the program `root`, making sure it stays inside of it
+pub fn resolve(root: &str, path: &str) -> std::io::Result<PathBuf> {
+    let relative = Path::new(path);
+
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            _ => {
+                return Err(input_error(format!(
+                    "{path} : only paths relative to the program folder without '..' are allowed"
+                )))
+            }
+        }
+    }
+
+    let root = fs::canonicalize(root)?;
+    let resolved = root.join(relative);
+
+    // symbolic links could still lead outside of the program folder
+    let mut existing = resolved.as_path();
+    while !existing.exists() {
+        existing = existing.parent().unwrap_or(&root);
+    }
+
+    if !fs::canonicalize(existing)?.starts_with(&root) {
+        return Err(input_error(format!(
+            "{path} : path leads outside of the program folder"
+        )));
+    }
+
+    Ok(resolved)
+}
+
+/// Create the folders of a literal expression holding `value`
+pub fn write_literal(folder: &Path, value: &Variable) -> std::io::Result<()> {
+    let bytes = match value {
+        Variable::Int(Some(value)) => value.to_ne_bytes().to_vec(),
+        Variable::Float(Some(value)) => value.to_ne_bytes().to_vec(),
+        Variable::String(Some(value)) => encode_chars(folder, value)?,
+        Variable::Char(Some(value)) => encode_chars(folder, &value.to_string())?,
+        Variable::Bool(Some(value)) => vec![*value as u8],
+        Variable::Array(..) => {
+            return Err(input_error(format!(
+                "{} : arrays have no literal value",
+                folder.display()
+            )))
+        }
+        _ => {
+            return Err(input_error(format!(
+                "{} : can not write a null value",
+                folder.display()
+            )))
+        }
+    };
+
+    fs::create_dir(folder)?;
+    // literal value expression
+    create_subfolders(&folder.join("0"), 5)?;
+    create_subfolders(&folder.join("1"), value.get_type().folder_count())?;
+
+    let value_folder = folder.join("2");
+    fs::create_dir(&value_folder)?;
+
+    let width = bytes.len().saturating_sub(1).to_string().len();
+    for (i, byte) in bytes.iter().enumerate() {
+        let byte_folder = value_folder.join(format!("{i:0width$}"));
+        fs::create_dir(&byte_folder)?;
+
+        for (digit, nibble) in [byte >> 4, byte & 0xf].iter().enumerate() {
+            let digit_folder = byte_folder.join(digit.to_string());
+            fs::create_dir(&digit_folder)?;
+
+            for bit in 0..4 {
+                let bit_folder = digit_folder.join(bit.to_string());
+                let bit_count = (nibble >> (3 - bit) & 1) as usize;
+                create_subfolders(&bit_folder, bit_count)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Strings and chars are stored one byte per character
+fn encode_chars(folder: &Path, value: &str) -> std::io::Result<Vec<u8>> {
+    value
+        .chars()
+        .map(|c| {
+            u8::try_from(c).map_err(|_| {
+                input_error(format!(
+                    "{} : '{c}' does not fit in a single byte",
+                    folder.display()
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Create `folder` holding `count` empty subfolders
+fn create_subfolders(folder: &Path, count: usize) -> std::io::Result<()> {
+    fs::create_dir(folder)?;
+
+    let width = count.saturating_sub(1).to_string().len();
+    for i in 0..count {
+        fs::create_dir(folder.join(format!("{i:0width$}")))?;
+    }
+
+    Ok(())
+}
@@ -37,64 +37,15 @@ pub fn subfolder_count(folder: &str) -> std::io::Result<usize> {
     let entries = fs::read_dir(folder)?;
 
     let mut count: usize = 0;
-    for entry in entries {
-        if let Ok(entry) = entry {
-            if is_directory(&entry) {
-                count += 1;
-            }
+    for entry in entries.flatten() {
+        if is_directory(&entry) {
+            count += 1;
         }
     }
 
     Ok(count)
 }
 
-/// used in context of literal values
-pub fn is_bit_set(folder: &str) -> bool {
-    subfolder_count(&folder).unwrap_or(0) != 0
-}
-
-/// read byte from folder
-pub fn get_byte(folder: &str) -> std::io::Result<u8> {
-    let hex_folders = sorted_subfolders(&folder)?;
-    if hex_folders.len() != 2 {
-        return Err(input_error(format!(
-            "{} : invalid byte, found {} subfolders",
-            folder,
-            hex_folders.len()
-        )));
-    }
-
-    let left = sorted_subfolders(&hex_folders[0])?;
-    let right = sorted_subfolders(&hex_folders[1])?;
-
-    if left.len() != 4 {
-        return Err(input_error(format!(
-            "{} : invalid hex digit, found {} subfolders",
-            hex_folders[0],
-            left.len()
-        )));
-    }
-
-    if right.len() != 4 {
-        return Err(input_error(format!(
-            "{} : invalid hex digit, found {} subfolders",
-            hex_folders[1],
-            right.len()
-        )));
-    }
-
-    let mut i = 7;
-    let mut byte: u8 = 0;
-
-    for bit_folder in left {
-        byte |= ((is_bit_set(&bit_folder) as i32) << i) as u8;
-        i -= 1;
-    }
-
-    for bit_folder in right {
-        byte |= ((is_bit_set(&bit_folder) as i32) << i) as u8;
-        i -= 1;
-    }
-
-    Ok(byte)
-}
+#[cfg(test)]
+#[path = "../tests/common/temp_dir.rs"]
+pub mod temp_dir;
@@ -0,0 +1,62 @@
+//! Polling of the folders of a program, to run it again when they change.
+
+use std::time::Duration;
+
+use crate::utils::sorted_subfolders;
+
+/// time between two looks at the folders
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait until a folder is created, removed or renamed below `folder`
+pub fn wait_for_change(folder: &str) {
+    let tree = folder_tree(folder);
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        if folder_tree(folder) != tree {
+            return;
+        }
+    }
+}
+
+/// Every folder below `folder`, the ones that can not be read are skipped
+fn folder_tree(folder: &str) -> Vec<String> {
+    let mut tree = vec![];
+    let mut pending = vec![folder.to_owned()];
+
+    while let Some(folder) = pending.pop() {
+        if let Ok(subfolders) = sorted_subfolders(&folder) {
+            tree.extend(subfolders.iter().cloned());
+            pending.extend(subfolders);
+        }
+    }
+
+    tree.sort();
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir::TempDir;
+
+    #[test]
+    fn folder_trees_change_with_their_folders() {
+        let dir = TempDir::new();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        let folder = root.to_str().unwrap();
+
+        let tree = folder_tree(folder);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(folder_tree(folder), tree);
+
+        // files are not part of a program
+        std::fs::write(root.join("a/.keep"), "").unwrap();
+        assert_eq!(folder_tree(folder), tree);
+
+        std::fs::rename(root.join("a/b"), root.join("a/c")).unwrap();
+        assert_ne!(folder_tree(folder), tree);
+    }
+}
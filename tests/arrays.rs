@@ -0,0 +1,126 @@
+//! Arrays are declared, read, updated and printed as values.
+
+mod common;
+
+use common::*;
+
+/// Declare var_`index` as an array of `element` type
+fn declare_array(element: usize, index: usize) -> Node {
+    l(vec![n(2), l(vec![n(element), n(0), n(0), n(0)]), n(index)])
+}
+
+fn index_get(sequence: Node, index: Node) -> Node {
+    l(vec![n(20), sequence, index])
+}
+
+fn index_set(array: Node, index: Node, value: Node) -> Node {
+    l(vec![n(21), array, index, value])
+}
+
+fn length(sequence: Node) -> Node {
+    l(vec![n(22), sequence])
+}
+
+fn push(array: Node, value: Node) -> Node {
+    l(vec![n(23), array, value])
+}
+
+fn numbers() -> Program {
+    Program::new(vec![
+        declare_array(0, 0),
+        print(var(0)),
+        newline(),
+        let_(0, push(var(0), int(1))),
+        let_(0, push(var(0), int(2))),
+        let_(0, push(var(0), int(3))),
+        print(var(0)),
+        newline(),
+        let_(0, index_set(var(0), int(1), int(20))),
+        print(index_get(var(0), int(1))),
+        newline(),
+        print(length(var(0))),
+        newline(),
+        // values are copied, var_0 is left untouched
+        let_(1, push(var(0), int(4))),
+        print(var(0)),
+        newline(),
+        print(var(1)),
+    ])
+}
+
+const NUMBERS: &str = "[]\n[1, 2, 3]\n20\n3\n[1, 20, 3]\n[1, 20, 3, 4]";
+
+#[test]
+fn arrays_are_values() {
+    let program = numbers();
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), NUMBERS);
+    assert_eq!(built(&["--extensions"], program.path(), ""), NUMBERS);
+}
+
+#[test]
+fn arrays_of_every_element_type() {
+    for (element, value, printed) in [
+        (1, float(1.5), "[1.5]"),
+        (2, string("ab"), "[ab]"),
+        (3, char('c'), "[c]"),
+    ] {
+        let program = Program::new(vec![
+            declare_array(element, 0),
+            let_(0, push(var(0), value)),
+            print(var(0)),
+        ]);
+
+        assert_eq!(stdout(&["run", "--extensions", program.path()]), printed);
+        assert_eq!(built(&["--extensions"], program.path(), ""), printed);
+    }
+}
+
+#[test]
+fn indexes_out_of_bounds_fail() {
+    for expression in [
+        index_get(var(0), int(1)),
+        index_get(var(0), int(-1)),
+        index_set(var(0), int(1), int(0)),
+    ] {
+        let program = Program::new(vec![
+            declare_array(0, 0),
+            let_(0, push(var(0), int(1))),
+            print(expression),
+        ]);
+        let output = folders(&["run", "--extensions", program.path()]);
+
+        assert!(stderr(&output).contains("is out of bounds for a Array of length 1"));
+    }
+}
+
+#[test]
+fn elements_have_the_type_of_the_array() {
+    let program = Program::new(vec![
+        declare_array(0, 0),
+        let_(0, push(var(0), string("a"))),
+    ]);
+
+    let output = folders(&["run", "--extensions", program.path()]);
+    assert!(stderr(&output).contains("can not store String in an Array of Int"));
+
+    let output = folders(&["check", "--extensions", program.path()]);
+    assert!(stderr(&output).contains("can not apply Push to [Array(Int), String]"));
+}
+
+#[test]
+fn arrays_need_extensions_and_are_not_webassembly() {
+    let program = numbers();
+
+    let output = folders(&["run", program.path()]);
+    assert!(stderr(&output).contains("Array type is an extension, run with --extensions"));
+
+    let output = folders(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+    assert!(stderr(&output).contains("wat target does not support arrays"));
+}
@@ -0,0 +1,70 @@
+//! Folder listings are read once per run unless `--no-cache` is given.
+
+mod common;
+
+use common::*;
+
+/// Number of directories read by a run, from its profile
+fn reads(options: &[&str], folder: &str) -> usize {
+    let args = [&["run", "--profile"], options, &[folder]].concat();
+    let output = folders_with_stdin(&args, "3\n4\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let profile = stderr(&output);
+    let header = profile.lines().next().unwrap();
+    header
+        .split(", ")
+        .nth(1)
+        .and_then(|reads| reads.split(' ').next())
+        .and_then(|count| count.parse().ok())
+        .unwrap_or_else(|| panic!("{header}"))
+}
+
+#[test]
+fn caching_does_not_change_outputs() {
+    for name in SAMPLES {
+        let cached = folders_with_stdin(&["run", &sample(name)], "3\n4\n");
+        let uncached = folders_with_stdin(&["run", "--no-cache", &sample(name)], "3\n4\n");
+
+        assert_eq!(cached.stdout, uncached.stdout, "{name}");
+        assert_eq!(cached.status.code(), uncached.status.code(), "{name}");
+    }
+}
+
+#[test]
+fn cached_runs_read_each_directory_once() {
+    let folder = sample("99Bottles");
+    let directories = walk(std::path::Path::new(&folder));
+
+    let cached = reads(&[], &folder);
+    let uncached = reads(&["--no-cache"], &folder);
+
+    // listings and counts of a same folder are cached apart
+    assert!(cached <= 2 * directories, "{cached} reads");
+    assert!(uncached > 10 * cached, "{uncached} reads without cache");
+}
+
+/// Number of directories in `folder`, itself included
+fn walk(folder: &std::path::Path) -> usize {
+    1 + std::fs::read_dir(folder)
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.file_type().unwrap().is_dir())
+        .map(|entry| walk(&entry.path()))
+        .sum::<usize>()
+}
+
+#[test]
+fn loops_do_not_read_their_folders_again() {
+    let program = |count| {
+        Program::new(vec![
+            let_(0, int(0)),
+            while_(lt(var(0), int(count)), vec![let_(0, add(var(0), int(1)))]),
+        ])
+    };
+    let short = program(2);
+    let long = program(200);
+
+    assert_eq!(reads(&[], short.path()), reads(&[], long.path()));
+    assert!(reads(&["--no-cache"], long.path()) > reads(&["--no-cache"], short.path()));
+}
@@ -0,0 +1,123 @@
+//! `Cast` converts values between types the same way on every backend.
+
+mod common;
+
+use common::*;
+
+const INT: usize = 0;
+const FLOAT: usize = 1;
+const STRING: usize = 2;
+const CHAR: usize = 3;
+const BOOL: usize = 5;
+
+fn cast(target: usize, value: Node) -> Node {
+    l(vec![n(24), n(target), value])
+}
+
+/// Program printing every expression on its own line, and the expected output
+fn program(cases: Vec<(Node, &str)>) -> (Program, String) {
+    let mut commands = vec![];
+    let mut expected = String::new();
+    for (expression, printed) in cases {
+        commands.extend([print(expression), newline()]);
+        expected += printed;
+        expected += "\n";
+    }
+
+    (Program::new(commands), expected)
+}
+
+/// Conversions supported by every backend
+fn numeric_cases() -> Vec<(Node, &'static str)> {
+    vec![
+        (cast(FLOAT, int(3)), "3"),
+        (cast(INT, float(2.7)), "2"),
+        (cast(INT, float(-2.7)), "-2"),
+        (cast(INT, char('A')), "65"),
+        (cast(BOOL, int(0)), "false"),
+        (cast(BOOL, float(0.5)), "true"),
+        (cast(BOOL, char('a')), "true"),
+        (cast(INT, boolean(true)), "1"),
+        (add(cast(FLOAT, int(1)), float(0.5)), "1.5"),
+    ]
+}
+
+fn string_cases() -> Vec<(Node, &'static str)> {
+    vec![
+        (cast(STRING, int(42)), "42"),
+        (cast(STRING, float(1.5)), "1.5"),
+        (add(cast(STRING, char('c')), string("d")), "cd"),
+        (cast(STRING, boolean(false)), "false"),
+        (cast(CHAR, int(66)), "B"),
+        (add(cast(INT, string(" 12 ")), int(1)), "13"),
+        (cast(FLOAT, string("2.5")), "2.5"),
+        (cast(BOOL, string("true")), "true"),
+        (cast(CHAR, string("x")), "x"),
+    ]
+}
+
+#[test]
+fn casts_run_and_build() {
+    let (program, expected) = program(numeric_cases().into_iter().chain(string_cases()).collect());
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), expected);
+    assert_eq!(built(&["--extensions"], program.path(), ""), expected);
+}
+
+#[test]
+fn numeric_casts_are_webassembly() {
+    let (program, expected) = program(numeric_cases());
+    let text = stdout(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+
+    let run = run_wat(&text, &[]);
+    assert!(!run.trapped);
+    assert_eq!(run.output, expected);
+}
+
+#[test]
+fn float_literals_are_compared_once_built() {
+    let (program, expected) = program(vec![
+        (lt(float(2.5), int(3)), "1"),
+        (gt(float(-1e20), float(1e-3)), "0"),
+        (lt(sub(float(1.0), float(-2.5)), float(3.6)), "1"),
+    ]);
+
+    assert_eq!(stdout(&["run", program.path()]), expected);
+    assert_eq!(built(&[], program.path(), ""), expected);
+}
+
+#[test]
+fn failing_casts_are_errors() {
+    for expression in [
+        cast(INT, div(float(0.0), float(0.0))),
+        cast(INT, float(1e10)),
+        cast(INT, string("abc")),
+        cast(BOOL, string("yes")),
+        cast(CHAR, string("xy")),
+    ] {
+        let program = Program::new(vec![print(expression)]);
+        let output = folders(&["run", "--extensions", program.path()]);
+
+        assert!(
+            !output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+}
+
+#[test]
+fn unsupported_casts_are_rejected_before_running() {
+    let program = Program::new(vec![print(cast(FLOAT, char('a')))]);
+
+    for command in ["run", "check"] {
+        let output = folders(&[command, "--extensions", program.path()]);
+        assert!(!output.status.success(), "{command}");
+    }
+}
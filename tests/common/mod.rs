@@ -0,0 +1,413 @@
+//! Helpers shared by the integration tests : running the `folders` binary,
+//! building programs in temporary folders and running WebAssembly modules.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+mod temp_dir;
+
+pub use temp_dir::TempDir;
+
+pub const SAMPLES: [&str; 5] = [
+    "99Bottles",
+    "AddTwoNumbersOrStrings",
+    "HelloWorld",
+    "SimpleEcho",
+    "test-translation",
+];
+
+pub fn sample(name: &str) -> String {
+    format!("{}/samples/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Run the binary with `args`, `stdin` is written to its standard input
+pub fn folders_with_stdin(args: &[&str], stdin: &str) -> Output {
+    use std::io::Write;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_folders"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // programs may exit without reading all of their input
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+
+    child.wait_with_output().unwrap()
+}
+
+pub fn folders(args: &[&str]) -> Output {
+    folders_with_stdin(args, "")
+}
+
+/// Standard output of the program in `folder` built with `options`, then run reading `stdin`
+pub fn built(options: &[&str], folder: &str, stdin: &str) -> String {
+    use std::io::Write;
+
+    let dir = TempDir::new();
+    let executable = dir.join("program");
+    stdout(&[&["build"], options, &["-o", &executable, folder]].concat());
+
+    let mut child = Command::new(&executable)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // programs may exit without reading all of their input
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{folder} built with {options:?} failed"
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Standard output of a run of the binary that must succeed
+pub fn stdout(args: &[&str]) -> String {
+    let output = folders(args);
+    assert!(
+        output.status.success(),
+        "folders {args:?} failed : {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Copy of the folders below `from` into `to`
+pub fn copy_tree(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+
+        if entry.file_type().unwrap().is_dir() {
+            copy_tree(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// Folder of a program : either a number of empty subfolders or a list of subfolders
+pub enum Node {
+    Count(usize),
+    List(Vec<Node>),
+}
+
+impl Node {
+    pub fn create(&self, path: &Path) {
+        std::fs::create_dir_all(path).unwrap();
+
+        match self {
+            Node::Count(count) => {
+                for i in 0..*count {
+                    std::fs::create_dir_all(path.join(format!("{i:03}"))).unwrap();
+                }
+            }
+            Node::List(nodes) => {
+                for (i, node) in nodes.iter().enumerate() {
+                    node.create(&path.join(format!("{i:03}")));
+                }
+            }
+        }
+    }
+}
+
+/// Program written to a temporary directory
+pub struct Program {
+    _dir: TempDir,
+    path: String,
+}
+
+impl Program {
+    pub fn new(commands: Vec<Node>) -> Self {
+        let dir = TempDir::new();
+        let path = dir.join("program");
+        Node::List(commands).create(Path::new(&path));
+
+        Program { _dir: dir, path }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+pub fn n(count: usize) -> Node {
+    Node::Count(count)
+}
+
+pub fn l(nodes: Vec<Node>) -> Node {
+    Node::List(nodes)
+}
+
+fn byte(value: u8) -> Node {
+    let bit = |i: u8| n(((value >> (7 - i)) & 1) as usize);
+    l(vec![
+        l((0..4).map(bit).collect()),
+        l((4..8).map(bit).collect()),
+    ])
+}
+
+fn literal(var_type: usize, bytes: &[u8]) -> Node {
+    l(vec![
+        n(5),
+        n(var_type),
+        l(bytes.iter().copied().map(byte).collect()),
+    ])
+}
+
+pub fn int(value: i32) -> Node {
+    literal(0, &value.to_le_bytes())
+}
+
+pub fn float(value: f32) -> Node {
+    literal(1, &value.to_le_bytes())
+}
+
+/// String literal, its chars must fit in a byte each
+pub fn string(value: &str) -> Node {
+    let chars: Vec<u8> = value.chars().map(|c| u8::try_from(c).unwrap()).collect();
+    literal(2, &chars)
+}
+
+pub fn char(value: char) -> Node {
+    literal(3, &[u8::try_from(value).unwrap()])
+}
+
+pub fn boolean(value: bool) -> Node {
+    literal(5, &[value as u8])
+}
+
+pub fn var(index: usize) -> Node {
+    l(vec![n(0), n(index)])
+}
+
+/// Expression `kind` of two operands
+pub fn binary(kind: usize, a: Node, b: Node) -> Node {
+    l(vec![n(kind), a, b])
+}
+
+pub fn add(a: Node, b: Node) -> Node {
+    binary(1, a, b)
+}
+
+pub fn sub(a: Node, b: Node) -> Node {
+    binary(2, a, b)
+}
+
+pub fn mul(a: Node, b: Node) -> Node {
+    binary(3, a, b)
+}
+
+pub fn div(a: Node, b: Node) -> Node {
+    binary(4, a, b)
+}
+
+pub fn gt(a: Node, b: Node) -> Node {
+    binary(7, a, b)
+}
+
+pub fn lt(a: Node, b: Node) -> Node {
+    binary(8, a, b)
+}
+
+pub fn modulo(a: Node, b: Node) -> Node {
+    binary(9, a, b)
+}
+
+pub fn int_divide(a: Node, b: Node) -> Node {
+    binary(26, a, b)
+}
+
+/// Call expression of the procedure `index`
+pub fn call(index: usize, arguments: Vec<Node>) -> Node {
+    l(vec![n(19), n(index), l(arguments)])
+}
+
+pub fn if_(condition: Node, body: Vec<Node>) -> Node {
+    l(vec![n(0), condition, l(body)])
+}
+
+pub fn while_(condition: Node, body: Vec<Node>) -> Node {
+    l(vec![n(1), condition, l(body)])
+}
+
+pub fn declare(var_type: usize, index: usize) -> Node {
+    l(vec![n(2), n(var_type), n(index)])
+}
+
+pub fn let_(index: usize, expression: Node) -> Node {
+    l(vec![n(3), n(index), expression])
+}
+
+pub fn print(expression: Node) -> Node {
+    l(vec![n(4), expression])
+}
+
+pub fn input(index: usize) -> Node {
+    l(vec![n(5), n(index)])
+}
+
+pub fn newline() -> Node {
+    print(char('\n'))
+}
+
+/// Extension command writing `expression` to the path given by `path`
+pub fn write(path: Node, expression: Node) -> Node {
+    l(vec![n(6), path, expression])
+}
+
+pub fn mkdir(path: Node) -> Node {
+    l(vec![n(7), path])
+}
+
+pub fn rmdir(path: Node) -> Node {
+    l(vec![n(8), path])
+}
+
+/// Procedure `index` taking `parameters` arguments
+pub fn define(index: usize, parameters: usize, body: Vec<Node>) -> Node {
+    l(vec![n(9), n(index), n(parameters), l(body)])
+}
+
+/// Call command of the procedure `index`
+pub fn call_command(index: usize, arguments: Vec<Node>) -> Node {
+    l(vec![n(10), n(index), l(arguments)])
+}
+
+pub fn return_(expression: Node) -> Node {
+    l(vec![n(11), expression])
+}
+
+/// Result of running the `main` export of a WebAssembly text module
+pub struct WasmRun {
+    pub output: String,
+    pub trapped: bool,
+}
+
+struct Host {
+    output: String,
+    input: VecDeque<String>,
+}
+
+/// Run a module transpiled with `--target wat`, `input` lines are read by its `input_*` imports
+pub fn run_wat(text: &str, input: &[&str]) -> WasmRun {
+    use wasmi::{Caller, Engine, Extern, Linker, Memory, Module, Store};
+
+    let bytes = wat::parse_str(text).expect("invalid WebAssembly text");
+    let engine = Engine::default();
+    let module = Module::new(&engine, &bytes[..]).expect("invalid WebAssembly module");
+
+    let host = Host {
+        output: String::new(),
+        input: input.iter().map(|line| line.to_string()).collect(),
+    };
+    let mut store = Store::new(&engine, host);
+    let mut linker = Linker::<Host>::new(&engine);
+
+    fn memory(caller: &Caller<'_, Host>) -> Memory {
+        match caller.get_export("memory") {
+            Some(Extern::Memory(memory)) => memory,
+            _ => panic!("memory is not exported"),
+        }
+    }
+    fn next_line(caller: &mut Caller<'_, Host>) -> String {
+        caller.data_mut().input.pop_front().unwrap_or_default()
+    }
+
+    linker
+        .func_wrap(
+            "env",
+            "print_i32",
+            |mut caller: Caller<'_, Host>, value: i32| {
+                caller.data_mut().output += &value.to_string();
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "env",
+            "print_f32",
+            |mut caller: Caller<'_, Host>, value: f32| {
+                caller.data_mut().output += &value.to_string();
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "env",
+            "print_char",
+            |mut caller: Caller<'_, Host>, value: i32| {
+                caller.data_mut().output.push(char::from(value as u8));
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "env",
+            "print_str",
+            |mut caller: Caller<'_, Host>, ptr: i32| {
+                let memory = memory(&caller);
+                let mut length = [0; 4];
+                memory.read(&caller, ptr as usize, &mut length).unwrap();
+                let mut bytes = vec![0; i32::from_le_bytes(length) as usize];
+                memory.read(&caller, ptr as usize + 4, &mut bytes).unwrap();
+
+                let text: String = bytes.into_iter().map(char::from).collect();
+                caller.data_mut().output += &text;
+            },
+        )
+        .unwrap()
+        .func_wrap("env", "input_i32", |mut caller: Caller<'_, Host>| -> i32 {
+            next_line(&mut caller).trim().parse().unwrap_or(0)
+        })
+        .unwrap()
+        .func_wrap("env", "input_f32", |mut caller: Caller<'_, Host>| -> f32 {
+            next_line(&mut caller).trim().parse().unwrap_or(0.0)
+        })
+        .unwrap()
+        .func_wrap("env", "input_char", |mut caller: Caller<'_, Host>| -> i32 {
+            next_line(&mut caller).bytes().next().unwrap_or(0) as i32
+        })
+        .unwrap()
+        .func_wrap(
+            "env",
+            "input_str",
+            |mut caller: Caller<'_, Host>, buffer: i32, capacity: i32| -> i32 {
+                let line = next_line(&mut caller);
+                let bytes = &line.as_bytes()[..line.len().min(capacity as usize)];
+                memory(&caller)
+                    .write(&mut caller, buffer as usize, bytes)
+                    .unwrap();
+                bytes.len() as i32
+            },
+        )
+        .unwrap();
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    let main = instance
+        .get_typed_func::<(), ()>(&store, "main")
+        .expect("main is not exported");
+    let trapped = main.call(&mut store, ()).is_err();
+
+    WasmRun {
+        output: std::mem::take(&mut store.data_mut().output),
+        trapped,
+    }
+}
@@ -0,0 +1,104 @@
+//! Comparisons are type-checked and give Bools with `--booleans`.
+
+mod common;
+
+use common::*;
+
+fn equal(a: Node, b: Node) -> Node {
+    binary(6, a, b)
+}
+
+/// Expression, what it prints without and with `--booleans`
+fn cases() -> Vec<(Node, &'static str, &'static str)> {
+    vec![
+        (gt(int(2), float(1.5)), "1", "true"),
+        (lt(float(2.5), int(2)), "0", "false"),
+        (equal(int(2), float(2.0)), "1", "true"),
+        (lt(string("abc"), string("abd")), "1", "true"),
+        (gt(char('b'), char('a')), "1", "true"),
+        (equal(string("a"), string("a")), "1", "true"),
+    ]
+}
+
+fn program(booleans: bool) -> (Program, String) {
+    let mut commands = vec![];
+    let mut expected = String::new();
+    for (expression, number, boolean) in cases() {
+        commands.extend([print(expression), newline()]);
+        expected += if booleans { boolean } else { number };
+        expected += "\n";
+    }
+
+    (Program::new(commands), expected)
+}
+
+#[test]
+fn comparisons_give_numbers() {
+    let (program, expected) = program(false);
+
+    assert_eq!(stdout(&["run", program.path()]), expected);
+    assert_eq!(built(&[], program.path(), ""), expected);
+}
+
+#[test]
+fn comparisons_give_bools_with_booleans() {
+    let (program, expected) = program(true);
+    let options = ["--extensions", "--booleans"];
+
+    assert_eq!(
+        stdout(&[&["run"], &options[..], &[program.path()]].concat()),
+        expected
+    );
+    assert_eq!(built(&options, program.path(), ""), expected);
+}
+
+#[test]
+fn values_of_different_types_are_not_compared() {
+    for expression in [
+        equal(int(1), string("1")),
+        gt(char('a'), int(1)),
+        lt(string("a"), float(1.0)),
+    ] {
+        let program = Program::new(vec![print(expression)]);
+
+        for command in ["run", "check"] {
+            let output = folders(&[command, program.path()]);
+            assert!(!output.status.success(), "{command}");
+        }
+    }
+}
+
+#[test]
+fn bool_literals_and_conditions() {
+    let program = Program::new(vec![
+        declare(5, 0),
+        let_(0, boolean(true)),
+        if_(var(0), vec![print(var(0))]),
+        if_(boolean(false), vec![print(int(1))]),
+    ]);
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "true");
+    assert_eq!(built(&["--extensions"], program.path(), ""), "true");
+
+    let output = folders(&["run", program.path()]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn bools_are_not_numbers() {
+    let program = Program::new(vec![print(add(boolean(true), int(1)))]);
+
+    for command in ["run", "check"] {
+        let output = folders(&[command, "--extensions", program.path()]);
+        assert!(!output.status.success(), "{command}");
+    }
+}
+
+#[test]
+fn booleans_need_extensions() {
+    let (program, _) = program(true);
+    let output = folders(&["run", "--booleans", program.path()]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--extensions"));
+}
@@ -0,0 +1,94 @@
+//! `--coverage` reports the commands run and the `if` bodies entered, by `disasm` line.
+
+mod common;
+
+use common::*;
+
+/// Prints the values of var_0 greater than 1, the second body is never entered
+fn branches() -> Program {
+    Program::new(vec![
+        let_(0, int(0)),
+        while_(
+            lt(var(0), int(4)),
+            vec![
+                if_(gt(var(0), int(1)), vec![print(var(0))]),
+                if_(gt(var(0), int(10)), vec![print(var(0))]),
+                let_(0, add(var(0), int(1))),
+            ],
+        ),
+    ])
+}
+
+/// Report of a run of `program` with `options`
+fn report(program: &Program, options: &[&str]) -> (std::process::Output, String) {
+    let dir = TempDir::new();
+    let file = dir.join("coverage");
+    let args = [&["run", "--coverage", &file], options, &[program.path()]].concat();
+
+    let output = folders(&args);
+    let report = std::fs::read_to_string(&file).unwrap();
+
+    (output, report)
+}
+
+#[test]
+fn lcov_reports_lines_and_branches() {
+    let program = branches();
+    let (output, report) = report(&program, &[]);
+
+    assert_eq!(output.stdout, b"23");
+    assert_eq!(
+        report,
+        format!(
+            "TN:\nSF:{}\n\
+             DA:1,1\nDA:2,1\n\
+             DA:3,4\nBRDA:3,0,0,2\nBRDA:3,0,1,2\nDA:4,2\n\
+             DA:6,4\nBRDA:6,0,0,0\nBRDA:6,0,1,4\nDA:7,0\n\
+             DA:9,4\n\
+             BRF:4\nBRH:3\nLF:7\nLH:6\nend_of_record\n",
+            program.path()
+        )
+    );
+}
+
+#[test]
+fn lines_are_the_ones_of_the_listing() {
+    let program = branches();
+    let listing = stdout(&["disasm", program.path()]);
+    let (_, report) = report(&program, &[]);
+
+    let lines: Vec<&str> = listing.lines().collect();
+    for line in report.lines().filter_map(|line| line.strip_prefix("DA:")) {
+        let number: usize = line.split(',').next().unwrap().parse().unwrap();
+        let command = lines[number - 1].trim();
+
+        assert!(!command.is_empty() && command != "}", "{line} : {command}");
+    }
+}
+
+#[test]
+fn html_reports_annotate_the_listing() {
+    let program = branches();
+    let (_, report) = report(&program, &["--coverage-format", "html"]);
+
+    assert!(report.contains("<p>6 of 7 commands run, 3 of 4 'if' branches taken</p>"));
+    assert_eq!(report.matches("<tr class=\"hit\"").count(), 5);
+    assert_eq!(report.matches("<tr class=\"partial\"").count(), 1);
+    assert_eq!(report.matches("<tr class=\"missed\"").count(), 1);
+    assert!(report.contains("<td>entered 2, skipped 2</td><td>    if var_0 &gt; 1 {</td>"));
+    assert!(report.contains("<td>while var_0 &lt; 4 {</td>"));
+}
+
+#[test]
+fn failing_runs_are_reported_up_to_the_error() {
+    let program = Program::new(vec![
+        print(int(1)),
+        print(int_divide(int(1), int(0))),
+        print(int(2)),
+    ]);
+    let (output, report) = report(&program, &["--extensions"]);
+
+    assert!(!output.status.success());
+    assert!(report.contains("DA:1,1\nDA:2,1\nDA:3,0\n"), "{report}");
+    assert!(report.contains("LF:3\nLH:2\n"), "{report}");
+}
@@ -0,0 +1,262 @@
+//! `diff` reads programs from folders and git revisions, and runs as a git diff driver.
+
+mod common;
+
+use common::*;
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+
+const BINARY: &str = env!("CARGO_BIN_EXE_folders");
+
+/// Git repository holding `programs/first` and `programs/second`, committed once
+struct Repository {
+    dir: TempDir,
+}
+
+impl Repository {
+    fn new() -> Self {
+        let repository = Repository {
+            dir: TempDir::new(),
+        };
+        repository.git(&["init", "-q"]);
+        repository.replace("programs/first", vec![print(int(1)), print(int(2))]);
+        repository.replace("programs/second", vec![print(string("a"))]);
+        repository.commit();
+
+        repository
+    }
+
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Program in `folder` replaced by `commands`, git keeps folders holding files
+    fn replace(&self, folder: &str, commands: Vec<Node>) {
+        let folder = self.path().join(folder);
+        let _ = std::fs::remove_dir_all(&folder);
+        copy_tree(Path::new(Program::new(commands).path()), &folder);
+        keep(&folder);
+    }
+
+    fn commit(&self) {
+        self.git(&["add", "-A"]);
+        self.git(&[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "-m",
+            "programs",
+        ]);
+    }
+
+    fn git(&self, args: &[&str]) -> Output {
+        self.git_with_driver(args, None)
+    }
+
+    /// `git args`, with `folders diff driver` as external diff
+    fn git_with_driver(&self, args: &[&str], driver: Option<&str>) -> Output {
+        let mut command = Command::new("git");
+        command
+            .args(args)
+            .current_dir(self.path())
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env_remove("GIT_EXTERNAL_DIFF");
+        if let Some(driver) = driver {
+            command.env("GIT_EXTERNAL_DIFF", format!("{BINARY} diff {driver}"));
+        }
+
+        let output = command.output().unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} : {}",
+            stderr(&output)
+        );
+
+        output
+    }
+
+    /// Output of `git diff args` through the driver
+    fn driver(&self, driver: &str, args: &[&str]) -> String {
+        let args = [&["diff"], args].concat();
+        let output = self.git_with_driver(&args, Some(driver));
+
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// `folders args` run from the repository
+    fn folders(&self, args: &[&str]) -> Output {
+        Command::new(BINARY)
+            .args(args)
+            .current_dir(self.path())
+            .output()
+            .unwrap()
+    }
+}
+
+/// A file in every empty folder below `folder`
+fn keep(folder: &Path) {
+    let mut empty = true;
+    for entry in std::fs::read_dir(folder).unwrap() {
+        empty = false;
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            keep(&entry.path());
+        }
+    }
+
+    if empty {
+        std::fs::write(folder.join(".keep"), "").unwrap();
+    }
+}
+
+fn headers(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| line.starts_with("folders diff "))
+        .collect()
+}
+
+#[test]
+fn samples_do_not_differ_from_themselves() {
+    for name in SAMPLES {
+        let output = folders(&["diff", &sample(name), &sample(name)]);
+
+        assert_eq!(output.status.code(), Some(0), "{name}");
+        assert!(output.stdout.is_empty(), "{name}");
+    }
+}
+
+#[test]
+fn different_programs_exit_with_1() {
+    let output = folders(&["diff", &sample("HelloWorld"), &sample("SimpleEcho")]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!output.stdout.is_empty());
+}
+
+#[test]
+fn local_folders_may_hold_colons() {
+    let dir = TempDir::new();
+    let folder = dir.join("HEAD:HelloWorld");
+    copy_tree(Path::new(&sample("HelloWorld")), Path::new(&folder));
+
+    assert_eq!(stdout(&["diff", &folder, &sample("HelloWorld")]), "");
+    assert_eq!(stdout(&["diff", &sample("HelloWorld"), &folder]), "");
+}
+
+#[test]
+fn programs_are_read_from_revisions() {
+    let repository = Repository::new();
+    repository.replace("programs/first", vec![print(int(1)), print(int(3))]);
+
+    let output = repository.folders(&["diff", "HEAD:programs/first", "programs/first"]);
+    let text = String::from_utf8(output.stdout.clone()).unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert!(text.starts_with("programs/first/"), "{text}");
+    assert!(text.contains("literal changed from 2 to 3"), "{text}");
+
+    let output = repository.folders(&["diff", "HEAD:programs/missing", "programs/first"]);
+    assert!(stderr(&output).contains("HEAD:programs/missing : no such folder in HEAD"));
+}
+
+#[test]
+fn driver_compares_each_changed_program_once() {
+    let repository = Repository::new();
+    repository.replace("programs/first", vec![print(int(7)), print(int(8))]);
+    repository.replace("programs/second", vec![print(string("b"))]);
+
+    let output = repository.driver("--root-depth 2", &[]);
+
+    assert_eq!(
+        headers(&output),
+        [
+            "folders diff HEAD:programs/first programs/first",
+            "folders diff HEAD:programs/second programs/second",
+        ]
+    );
+    assert!(output.contains("literal changed from 1 to 7"), "{output}");
+    assert!(output.contains("literal changed from 2 to 8"), "{output}");
+    assert!(
+        output.contains("literal changed from \"a\" to \"b\""),
+        "{output}"
+    );
+    assert!(!output.contains("folders-diff-"), "{output}");
+}
+
+#[test]
+fn driver_compares_revisions() {
+    let repository = Repository::new();
+    repository.replace("programs/first", vec![print(int(1)), print(int(5))]);
+    repository.commit();
+
+    let output = repository.driver(
+        "--root-depth 2 --old-revision HEAD~1 --new-revision HEAD",
+        &["HEAD~1", "HEAD"],
+    );
+
+    assert_eq!(
+        headers(&output),
+        ["folders diff HEAD~1:programs/first HEAD:programs/first"]
+    );
+    assert!(output.contains("HEAD:programs/first/"), "{output}");
+    assert!(output.contains("literal changed from 2 to 5"), "{output}");
+}
+
+#[test]
+fn driver_compares_added_and_removed_programs() {
+    let repository = Repository::new();
+    std::fs::remove_dir_all(repository.path().join("programs/second")).unwrap();
+    repository.replace("programs/third", vec![print(int(3))]);
+    repository.git(&["add", "-N", "programs/third"]);
+
+    let output = repository.driver("--root-depth 2", &[]);
+
+    assert_eq!(
+        headers(&output),
+        [
+            "folders diff HEAD:programs/second programs/second",
+            "folders diff HEAD:programs/third programs/third",
+        ]
+    );
+    assert!(output.contains("removed"), "{output}");
+    assert!(output.contains("inserted"), "{output}");
+}
+
+#[test]
+fn driver_reports_invalid_programs_without_failing() {
+    let repository = Repository::new();
+    repository.replace("programs/first", vec![l(vec![n(0), n(0)])]);
+
+    let output = repository.driver("--root-depth 2", &[]);
+
+    assert_eq!(
+        headers(&output),
+        ["folders diff HEAD:programs/first programs/first"]
+    );
+    assert!(output.contains("error: programs/first/"), "{output}");
+}
+
+#[test]
+fn driver_ignores_files_outside_of_programs() {
+    let repository = Repository::new();
+    std::fs::write(repository.path().join("programs/notes"), "").unwrap();
+    repository.commit();
+    std::fs::write(repository.path().join("programs/notes"), "changed").unwrap();
+
+    assert_eq!(repository.driver("--root-depth 2", &[]), "");
+}
+
+#[test]
+fn other_argument_counts_are_rejected() {
+    let output = folders(&["diff", "a", "b", "c"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("expected 2 programs"));
+}
@@ -0,0 +1,90 @@
+//! `if` commands with a fourth folder run it when their condition is false.
+
+mod common;
+
+use common::*;
+
+fn if_else(condition: Node, body: Vec<Node>, otherwise: Vec<Node>) -> Node {
+    l(vec![n(0), condition, l(body), l(otherwise)])
+}
+
+/// Prints whether 0, 1 and 2 are greater than 0
+fn branches() -> Program {
+    Program::new(vec![
+        let_(0, int(0)),
+        while_(
+            lt(var(0), int(3)),
+            vec![
+                if_else(
+                    gt(var(0), int(0)),
+                    vec![print(string("yes"))],
+                    vec![print(string("no"))],
+                ),
+                print(char(' ')),
+                let_(0, add(var(0), int(1))),
+            ],
+        ),
+    ])
+}
+
+#[test]
+fn else_bodies_run_when_the_condition_is_false() {
+    let program = branches();
+
+    assert_eq!(
+        stdout(&["run", "--extensions", program.path()]),
+        "no yes yes "
+    );
+    assert_eq!(built(&["--extensions"], program.path(), ""), "no yes yes ");
+
+    let text = stdout(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+    assert_eq!(run_wat(&text, &[]).output, "no yes yes ");
+}
+
+#[test]
+fn else_bodies_are_disassembled() {
+    let program = Program::new(vec![if_else(
+        gt(int(1), int(0)),
+        vec![print(int(1))],
+        vec![print(int(2))],
+    )]);
+
+    assert_eq!(
+        stdout(&["disasm", "--extensions", program.path()]),
+        "if 1 > 0 {\n    print 1\n} else {\n    print 2\n}\n"
+    );
+}
+
+#[test]
+fn else_bodies_have_their_own_scope() {
+    let program = Program::new(vec![
+        let_(0, int(1)),
+        if_else(int(0), vec![], vec![let_(0, int(2)), let_(1, int(3))]),
+        print(var(0)),
+    ]);
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "2");
+    assert_eq!(built(&["--extensions"], program.path(), ""), "2");
+
+    let program = Program::new(vec![
+        if_else(int(0), vec![], vec![let_(1, int(3))]),
+        print(var(1)),
+    ]);
+    for command in ["run", "check"] {
+        let output = folders(&[command, "--extensions", program.path()]);
+        assert!(!output.status.success(), "{command}");
+    }
+}
+
+#[test]
+fn else_bodies_need_extensions() {
+    let program = branches();
+    let output = folders(&["run", program.path()]);
+
+    assert!(stderr(&output).contains("'else' body is an extension, run with --extensions"));
+}
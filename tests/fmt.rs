@@ -0,0 +1,82 @@
+//! `fmt` renames the mixed-style folders of the samples without changing what they do.
+
+mod common;
+
+use common::*;
+use std::path::Path;
+
+/// Copy of a sample in a temporary directory
+fn copy_sample(name: &str) -> (TempDir, String) {
+    let dir = TempDir::new();
+    let folder = dir.join(name);
+    copy_tree(Path::new(&sample(name)), Path::new(&folder));
+
+    (dir, folder)
+}
+
+fn run(folder: &str) -> String {
+    stdout(&[
+        "run",
+        "--input",
+        "3",
+        "--input",
+        "4",
+        "--input-policy",
+        "string",
+        folder,
+    ])
+}
+
+#[test]
+fn samples_are_not_formatted() {
+    for name in SAMPLES {
+        let output = folders(&["fmt", "--check", &sample(name)]);
+
+        assert_eq!(output.status.code(), Some(1), "{name}");
+        assert!(String::from_utf8_lossy(&output.stdout).contains("should be named"));
+    }
+}
+
+#[test]
+fn formatted_samples_pass_check() {
+    for name in SAMPLES {
+        let (_dir, folder) = copy_sample(name);
+
+        stdout(&["fmt", &folder]);
+        assert_eq!(stdout(&["fmt", "--check", &folder]), "", "{name}");
+    }
+}
+
+#[test]
+fn formatting_keeps_programs_unchanged() {
+    for name in SAMPLES {
+        let (_dir, folder) = copy_sample(name);
+        let listing = stdout(&["disasm", &folder]);
+        let output = run(&folder);
+
+        stdout(&["fmt", &folder]);
+
+        assert_eq!(stdout(&["disasm", &folder]), listing, "{name}");
+        assert_eq!(run(&folder), output, "{name}");
+        assert_eq!(stdout(&["diff", &sample(name), &folder]), "", "{name}");
+    }
+}
+
+#[test]
+fn formatting_twice_changes_nothing() {
+    let (_dir, folder) = copy_sample("99Bottles");
+
+    stdout(&["fmt", &folder]);
+    let names = std::fs::read_dir(&folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<std::collections::BTreeSet<_>>();
+    stdout(&["fmt", &folder]);
+
+    let renamed = std::fs::read_dir(&folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(renamed, names);
+    assert!(names.iter().any(|name| name == "0 - let"));
+}
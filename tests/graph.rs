@@ -0,0 +1,82 @@
+//! `graph` renders the syntax tree of a program in the DOT language.
+
+mod common;
+
+use common::*;
+
+fn lines(dot: &str) -> Vec<&str> {
+    dot.lines().map(str::trim).collect()
+}
+
+#[test]
+fn samples_are_rendered_as_syntax_trees() {
+    let dot = stdout(&["graph", &sample("AddTwoNumbersOrStrings")]);
+
+    assert_eq!(
+        lines(&dot),
+        [
+            "digraph program {",
+            "ordering=out;",
+            "node [fontname=\"monospace\"];",
+            "n0 [label=\"program\", shape=box, style=\"bold\"];",
+            "n1 [label=\"input var_0\", shape=box, style=\"rounded\"];",
+            "n0 -> n1;",
+            "n2 [label=\"input var_1\", shape=box, style=\"rounded\"];",
+            "n0 -> n2;",
+            "n3 [label=\"print\", shape=box, style=\"rounded\"];",
+            "n4 [label=\"Add\", shape=ellipse, style=\"solid\"];",
+            "n5 [label=\"var_0\", shape=ellipse, style=\"solid\"];",
+            "n6 [label=\"var_1\", shape=ellipse, style=\"solid\"];",
+            "n4 -> n5;",
+            "n4 -> n6;",
+            "n3 -> n4;",
+            "n0 -> n3;",
+            "}",
+        ]
+    );
+}
+
+#[test]
+fn literals_are_decoded_and_escaped() {
+    let dot = stdout(&["graph", &sample("HelloWorld")]);
+    assert!(
+        dot.contains("[label=\"\\\"Hello, World!\\\" : String\", shape=ellipse, style=\"filled\"]")
+    );
+
+    let program = Program::new(vec![declare(1, 0), let_(0, float(1.5)), print(char('\n'))]);
+    let dot = stdout(&["graph", program.path()]);
+    assert!(dot.contains("label=\"declare var_0 : f32\""), "{dot}");
+    assert!(dot.contains("label=\"1.5 : f32\""), "{dot}");
+    assert!(dot.contains("label=\"'\\\\n' : char\""), "{dot}");
+}
+
+#[test]
+fn bodies_and_conditions_are_labelled() {
+    let program = Program::new(vec![
+        let_(0, int(0)),
+        while_(lt(var(0), int(2)), vec![let_(0, add(var(0), int(1)))]),
+    ]);
+    let dot = stdout(&["graph", program.path()]);
+
+    assert!(dot.contains("label=\"while\""), "{dot}");
+    assert_eq!(dot.matches("[label=\"condition\"]").count(), 1, "{dot}");
+    assert_eq!(dot.matches("[label=\"body\"]").count(), 1, "{dot}");
+}
+
+#[test]
+fn folders_are_shown_behind_nodes() {
+    let plain = stdout(&["graph", &sample("99Bottles")]);
+    let dot = stdout(&["graph", "--folders", &sample("99Bottles")]);
+
+    let nodes = plain
+        .lines()
+        .filter(|line| line.contains(" [label=") && !line.contains(" -> "))
+        .count();
+    assert_eq!(dot.matches("shape=folder").count(), nodes);
+    assert_eq!(dot.matches("arrowhead=none").count(), nodes);
+
+    for tooltip in dot.split("tooltip=\"").skip(1) {
+        let folder = tooltip.split('"').next().unwrap();
+        assert!(std::path::Path::new(folder).is_dir(), "{folder}");
+    }
+}
@@ -0,0 +1,95 @@
+//! `input` commands read lines from `--input-file` and `--input` before stdin.
+
+mod common;
+
+use common::*;
+
+/// Reads three lines and prints them separated by commas
+fn three_lines() -> Program {
+    Program::new(vec![
+        input(0),
+        input(1),
+        input(2),
+        print(var(0)),
+        print(char(',')),
+        print(var(1)),
+        print(char(',')),
+        print(var(2)),
+    ])
+}
+
+fn run(program: &Program, options: &[&str], stdin: &str) -> std::process::Output {
+    let args = [&["run"], options, &[program.path()]].concat();
+
+    folders_with_stdin(&args, stdin)
+}
+
+#[test]
+fn lines_are_read_from_stdin_by_default() {
+    let output = run(&three_lines(), &[], "a\nb\nc\n");
+
+    assert_eq!(output.stdout, b"a,b,c");
+}
+
+#[test]
+fn given_lines_are_read_instead_of_stdin() {
+    let output = run(
+        &three_lines(),
+        &["--input", "a", "--input", "b", "--input", "c"],
+        "stdin\n",
+    );
+
+    assert_eq!(output.stdout, b"a,b,c");
+}
+
+#[test]
+fn file_lines_come_before_arguments() {
+    let dir = TempDir::new();
+    let file = dir.join("input.txt");
+    std::fs::write(&file, "first\nsecond\n").unwrap();
+
+    let output = run(
+        &three_lines(),
+        &["--input", "third", "--input-file", &file],
+        "",
+    );
+
+    assert_eq!(output.stdout, b"first,second,third");
+}
+
+#[test]
+fn exhausted_input_reads_empty_lines() {
+    let output = run(&three_lines(), &["--input", "a"], "stdin\n");
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"a,,");
+}
+
+#[test]
+fn strict_input_fails_once_exhausted() {
+    let output = run(&three_lines(), &["--input", "a", "--strict-input"], "");
+    assert!(!output.status.success());
+
+    let output = run(
+        &three_lines(),
+        &[
+            "--strict-input",
+            "--input",
+            "a",
+            "--input",
+            "b",
+            "--input",
+            "c",
+        ],
+        "",
+    );
+    assert_eq!(output.stdout, b"a,b,c");
+}
+
+#[test]
+fn missing_input_files_are_errors() {
+    let output = run(&three_lines(), &["--input-file", "/nonexistent/input"], "");
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
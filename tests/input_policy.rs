@@ -0,0 +1,90 @@
+//! `--input-policy` decides the type of the values read by `input` commands.
+
+mod common;
+
+use common::*;
+
+fn run(program: &Program, policy: &str, lines: &[&str]) -> std::process::Output {
+    let mut args = vec!["run", "--extensions", "--input-policy", policy];
+    for line in lines {
+        args.push("--input");
+        args.push(line);
+    }
+    args.push(program.path());
+
+    folders(&args)
+}
+
+/// Reads two lines and prints their sum
+fn sum() -> Program {
+    Program::new(vec![input(0), input(1), print(add(var(0), var(1)))])
+}
+
+/// Declares var_0 with `var_type`, reads it and prints it
+fn typed(var_type: usize) -> Program {
+    Program::new(vec![declare(var_type, 0), input(0), print(var(0))])
+}
+
+#[test]
+fn inferred_lines_are_numbers_chars_or_strings() {
+    for (lines, printed) in [
+        (["3", "4"], "7"),
+        ([" 3 ", "0.5"], "3.5"),
+        (["a", "b"], "ab"),
+        (["ab", "c"], "abc"),
+    ] {
+        let output = run(&sum(), "infer", &lines);
+
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            printed,
+            "{lines:?}"
+        );
+    }
+}
+
+#[test]
+fn string_lines_are_kept_as_they_are() {
+    let output = run(&sum(), "string", &["3", " 4 "]);
+
+    assert_eq!(output.stdout, b"3 4 ");
+}
+
+#[test]
+fn typed_lines_take_the_declared_type() {
+    for (var_type, line, printed) in [
+        (0, " 12 ", "12"),
+        (1, "2.5", "2.5"),
+        (2, "12", "12"),
+        (3, "x", "x"),
+        (5, "true", "true"),
+    ] {
+        let output = run(&typed(var_type), "typed", &[line]);
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), printed, "{line}");
+    }
+
+    let program = Program::new(vec![
+        declare(2, 0),
+        declare(2, 1),
+        input(0),
+        input(1),
+        print(add(var(0), var(1))),
+    ]);
+    assert_eq!(run(&program, "typed", &["3", "4"]).stdout, b"34");
+}
+
+#[test]
+fn typed_lines_must_convert() {
+    for (var_type, line) in [(0, "abc"), (0, "1.5"), (3, "xy"), (5, "yes")] {
+        let output = run(&typed(var_type), "typed", &[line]);
+
+        assert!(!output.status.success(), "{line}");
+    }
+
+    let output = run(&sum(), "typed", &["3", "4"]);
+    assert!(
+        stderr(&output).contains("var_0 must be declared to be read with the typed input policy")
+    );
+}
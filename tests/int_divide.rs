@@ -0,0 +1,58 @@
+//! `Int Divide` keeps Ints and rounds toward zero, `Divide` gives Floats.
+
+mod common;
+
+use common::*;
+
+fn program() -> (Program, &'static str) {
+    let program = Program::new(vec![
+        print(int_divide(int(7), int(2))),
+        newline(),
+        print(int_divide(int(-7), int(2))),
+        newline(),
+        print(div(int(7), int(2))),
+        newline(),
+        print(add(int_divide(int(9), int(3)), int(1))),
+    ]);
+
+    (program, "3\n-3\n3.5\n4")
+}
+
+#[test]
+fn int_division_rounds_toward_zero() {
+    let (program, expected) = program();
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), expected);
+    assert_eq!(built(&["--extensions"], program.path(), ""), expected);
+
+    let text = stdout(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+    assert_eq!(run_wat(&text, &[]).output, expected);
+}
+
+#[test]
+fn int_division_only_applies_to_ints() {
+    for expression in [
+        int_divide(float(7.0), int(2)),
+        int_divide(int(7), float(2.0)),
+    ] {
+        let program = Program::new(vec![print(expression)]);
+
+        for command in ["run", "check"] {
+            let output = folders(&[command, "--extensions", program.path()]);
+            assert!(!output.status.success(), "{command}");
+        }
+    }
+}
+
+#[test]
+fn int_division_needs_extensions() {
+    let (program, _) = program();
+
+    assert!(!folders(&["run", program.path()]).status.success());
+}
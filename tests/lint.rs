@@ -0,0 +1,112 @@
+//! `lint` warns about layouts the interpreter accepts but that are easy to break.
+
+mod common;
+
+use common::*;
+use std::path::Path;
+
+fn lint(program: &Program) -> Vec<String> {
+    stdout(&["lint", program.path()])
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn rename(program: &Program, from: &str, to: &str) {
+    let root = Path::new(program.path());
+    std::fs::rename(root.join(from), root.join(to)).unwrap();
+}
+
+/// Program printing var_0
+fn printing() -> Program {
+    Program::new(vec![let_(0, int(1)), print(var(0))])
+}
+
+#[test]
+fn zero_padded_programs_have_no_warnings() {
+    assert!(lint(&printing()).is_empty());
+}
+
+#[test]
+fn names_only_differing_by_case_are_reported() {
+    let program = printing();
+    rename(&program, "000", "a");
+    rename(&program, "001", "A");
+
+    let warnings = lint(&program);
+
+    assert!(
+        warnings.iter().any(|warning| warning
+            .ends_with("only differ by case, their order depends on the file system")),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn names_not_read_alphabetically_are_reported() {
+    let program = printing();
+    rename(&program, "000", "9");
+    rename(&program, "001", "10");
+
+    assert_eq!(stdout(&["run", program.path()]), "1");
+    assert_eq!(
+        lint(&program),
+        [format!(
+            "warning: {} : '9' is read before '10' although it is listed after it alphabetically, prefer zero-padded names",
+            program.path()
+        )]
+    );
+}
+
+#[test]
+fn files_in_command_folders_are_reported() {
+    let program = printing();
+    let command = Path::new(program.path()).join("001");
+    std::fs::write(command.join("notes.txt"), "").unwrap();
+
+    assert_eq!(
+        lint(&program),
+        [format!(
+            "warning: {} : 'notes.txt' is not a folder and is ignored",
+            command.display()
+        )]
+    );
+}
+
+#[test]
+fn unread_variables_are_reported() {
+    let program = Program::new(vec![declare(0, 3), let_(0, int(1)), print(var(0))]);
+
+    let warnings = lint(&program);
+
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].ends_with("var_3 is declared but never read"));
+}
+
+#[test]
+fn bit_folders_that_are_not_plain_are_reported() {
+    let program = printing();
+    // first bit of the literal of 'let'
+    let bit = Path::new(program.path()).join("000/002/002/000/000/000");
+    std::fs::create_dir_all(bit.join("a")).unwrap();
+    std::fs::create_dir_all(bit.join("b")).unwrap();
+
+    let warnings = lint(&program);
+    assert_eq!(
+        warnings,
+        [format!(
+            "warning: {} : bit folder holds 2 subfolders, it is read as 1",
+            bit.display()
+        )]
+    );
+
+    std::fs::remove_dir(bit.join("b")).unwrap();
+    std::fs::create_dir(bit.join("a/nested")).unwrap();
+    assert_eq!(
+        lint(&program),
+        [format!(
+            "warning: {} : bit folder holds nested subfolders, it is read as 1",
+            bit.display()
+        )]
+    );
+}
@@ -0,0 +1,277 @@
+//! Int arithmetic at the i32 boundaries gives the same results in every numeric
+//! mode through the interpreter, the optimizer and both transpile targets.
+
+mod common;
+
+use common::*;
+
+const MODES: [&str; 3] = ["wrapping", "checked", "saturating"];
+
+const OPERATORS: [&str; 6] = ["+", "-", "*", "/", "//", "%"];
+
+const OPERANDS: [(i32, i32); 12] = [
+    (i32::MAX, 1),
+    (i32::MAX, -1),
+    (i32::MAX, i32::MAX),
+    (i32::MAX, 2),
+    (i32::MAX, 0),
+    (i32::MIN, 1),
+    (i32::MIN, -1),
+    (i32::MIN, i32::MIN),
+    (i32::MIN, i32::MAX),
+    (i32::MIN, 2),
+    (i32::MIN, 0),
+    (0, 0),
+];
+
+fn operation(operator: &str, a: Node, b: Node) -> Node {
+    match operator {
+        "+" => add(a, b),
+        "-" => sub(a, b),
+        "*" => mul(a, b),
+        "/" => div(a, b),
+        "//" => int_divide(a, b),
+        _ => modulo(a, b),
+    }
+}
+
+/// Printed result of `a operator b`, None when the program has to fail
+fn expected(mode: &str, operator: &str, a: i32, b: i32) -> Option<String> {
+    let exact = match operator {
+        "+" => a as i64 + b as i64,
+        "-" => a as i64 - b as i64,
+        "*" => a as i64 * b as i64,
+        "/" if b == 0 && mode == "checked" => return None,
+        "/" => return Some((a as f32 / b as f32).to_string()),
+        "%" if b == 0 => return None,
+        "%" => return Some(a.wrapping_rem(b).to_string()),
+        _ if b == 0 => return None,
+        _ => a as i64 / b as i64,
+    };
+
+    let value = match mode {
+        "wrapping" => exact as i32,
+        "checked" => i32::try_from(exact).ok()?,
+        _ => exact.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+    };
+
+    Some(value.to_string())
+}
+
+/// Program reading two Ints and the index of an operator, printing the result
+fn calculator() -> Program {
+    let mut commands = vec![declare(0, 0), declare(0, 1), declare(0, 2)];
+    commands.extend([input(0), input(1), input(2)]);
+
+    for (i, operator) in OPERATORS.iter().enumerate() {
+        commands.push(if_(
+            l(vec![n(6), var(2), int(i as i32)]),
+            vec![print(operation(operator, var(0), var(1)))],
+        ));
+    }
+
+    Program::new(commands)
+}
+
+fn input_lines(operator: &str, a: i32, b: i32) -> [String; 3] {
+    let index = OPERATORS.iter().position(|op| *op == operator).unwrap();
+
+    [a.to_string(), b.to_string(), index.to_string()]
+}
+
+/// Printed result, None when the run failed
+fn interpreted(program: &str, mode: &str, lines: &[String], optimize: bool) -> Option<String> {
+    let mut args = vec![
+        "run",
+        "--extensions",
+        "--numeric",
+        mode,
+        "--input-policy",
+        "typed",
+    ];
+    // '--input -1' would be read as an option
+    let inputs: Vec<String> = lines.iter().map(|line| format!("--input={line}")).collect();
+    args.extend(inputs.iter().map(String::as_str));
+    if optimize {
+        args.push("-O");
+    }
+    args.push(program);
+
+    let output = folders(&args);
+    match output.status.code() {
+        Some(0) => Some(String::from_utf8(output.stdout).unwrap()),
+        Some(1) => None,
+        code => panic!("unexpected exit code {code:?} : {}", stderr(&output)),
+    }
+}
+
+fn wasm(text: &str, lines: &[String]) -> Option<String> {
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let run = run_wat(text, &lines);
+
+    (!run.trapped).then_some(run.output)
+}
+
+fn transpile_wat(program: &str, mode: &str, optimize: bool) -> String {
+    let mut args = vec![
+        "transpile",
+        "--target",
+        "wat",
+        "--extensions",
+        "--numeric",
+        mode,
+    ];
+    if optimize {
+        args.push("-O");
+    }
+    args.push(program);
+
+    stdout(&args)
+}
+
+fn build(program: &str, mode: &str, optimize: bool, dir: &TempDir) -> String {
+    let executable = dir.join(&format!("{mode}-{optimize}"));
+    let mut args = vec![
+        "build",
+        "--extensions",
+        "--numeric",
+        mode,
+        "-o",
+        &executable,
+    ];
+    if optimize {
+        args.push("-O");
+    }
+    args.push(program);
+    stdout(&args);
+
+    executable
+}
+
+fn executed(executable: &str, lines: &[String]) -> Option<String> {
+    let output = std::process::Command::new(executable)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .and_then(|mut child| {
+            use std::io::Write;
+            // failing programs may exit before reading all of their input
+            let _ = child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all((lines.join("\n") + "\n").as_bytes());
+            child.wait_with_output()
+        })
+        .unwrap();
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn interpreter_at_boundaries() {
+    let program = calculator();
+
+    for mode in MODES {
+        for operator in OPERATORS {
+            for (a, b) in OPERANDS {
+                let lines = input_lines(operator, a, b);
+
+                assert_eq!(
+                    interpreted(program.path(), mode, &lines, false),
+                    expected(mode, operator, a, b),
+                    "{a} {operator} {b} in {mode} mode"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn optimizer_at_boundaries() {
+    for mode in MODES {
+        for operator in OPERATORS {
+            for (a, b) in OPERANDS {
+                let program = Program::new(vec![print(operation(operator, int(a), int(b)))]);
+
+                assert_eq!(
+                    interpreted(program.path(), mode, &[], true),
+                    expected(mode, operator, a, b),
+                    "{a} {operator} {b} in {mode} mode, optimized"
+                );
+                assert_eq!(
+                    wasm(&transpile_wat(program.path(), mode, true), &[]),
+                    expected(mode, operator, a, b),
+                    "{a} {operator} {b} in {mode} mode, optimized to wat"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn wat_at_boundaries() {
+    let program = calculator();
+
+    for mode in MODES {
+        let text = transpile_wat(program.path(), mode, false);
+
+        for operator in OPERATORS {
+            for (a, b) in OPERANDS {
+                assert_eq!(
+                    wasm(&text, &input_lines(operator, a, b)),
+                    expected(mode, operator, a, b),
+                    "{a} {operator} {b} in {mode} mode, as wat"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn rust_at_boundaries() {
+    let program = calculator();
+    let dir = TempDir::new();
+
+    for mode in MODES {
+        let executable = build(program.path(), mode, false, &dir);
+
+        for operator in OPERATORS {
+            for (a, b) in OPERANDS {
+                assert_eq!(
+                    executed(&executable, &input_lines(operator, a, b)),
+                    expected(mode, operator, a, b),
+                    "{a} {operator} {b} in {mode} mode, as rust"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn rust_optimized_at_boundaries() {
+    let dir = TempDir::new();
+
+    for mode in MODES {
+        // operations that fail stop the program, they are left out
+        let mut commands = vec![];
+        let mut lines = String::new();
+        for operator in OPERATORS {
+            for (a, b) in OPERANDS {
+                if let Some(value) = expected(mode, operator, a, b) {
+                    commands.push(print(operation(operator, int(a), int(b))));
+                    commands.push(newline());
+                    lines += &format!("{value}\n");
+                }
+            }
+        }
+        let program = Program::new(commands);
+        let executable = build(program.path(), mode, true, &dir);
+
+        assert_eq!(executed(&executable, &[]), Some(lines), "{mode} mode");
+    }
+}
@@ -0,0 +1,131 @@
+//! Modulo, logical and bitwise expressions give the same results on every backend.
+
+mod common;
+
+use common::*;
+
+fn not(a: Node) -> Node {
+    l(vec![n(13), a])
+}
+
+/// Expression, what it prints
+fn cases() -> Vec<(Node, &'static str)> {
+    vec![
+        (modulo(int(7), int(3)), "1"),
+        (modulo(int(-7), int(3)), "-1"),
+        (binary(10, int(1), int(2)), "1"),
+        (binary(10, int(2), int(2)), "0"),
+        (binary(11, int(2), int(3)), "1"),
+        (binary(11, int(1), int(0)), "0"),
+        (binary(12, int(0), int(0)), "0"),
+        (binary(12, int(0), int(5)), "1"),
+        (not(int(0)), "1"),
+        (not(int(7)), "0"),
+        (binary(14, int(12), int(10)), "8"),
+        (binary(15, int(12), int(10)), "14"),
+        (binary(16, int(12), int(10)), "6"),
+        (binary(17, int(1), int(4)), "16"),
+        (binary(18, int(-16), int(2)), "-4"),
+    ]
+}
+
+/// Program printing every case on its own line, and the expected output
+fn program() -> (Program, String) {
+    let (expressions, outputs): (Vec<Node>, Vec<&str>) = cases().into_iter().unzip();
+    let commands = expressions
+        .into_iter()
+        .flat_map(|expression| [print(expression), newline()])
+        .collect();
+
+    (Program::new(commands), outputs.join("\n") + "\n")
+}
+
+#[test]
+fn interpreter_computes_operators() {
+    let (program, expected) = program();
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), expected);
+}
+
+#[test]
+fn built_programs_compute_operators() {
+    let (program, expected) = program();
+
+    assert_eq!(built(&["--extensions"], program.path(), ""), expected);
+}
+
+#[test]
+fn webassembly_computes_operators() {
+    let (program, expected) = program();
+    let text = stdout(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+
+    let run = run_wat(&text, &[]);
+    assert!(!run.trapped);
+    assert_eq!(run.output, expected);
+}
+
+#[test]
+fn operators_need_extensions() {
+    let program = Program::new(vec![print(modulo(int(7), int(3)))]);
+    let output = folders(&["run", program.path()]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn bitwise_operators_only_apply_to_ints() {
+    for kind in 14..=18 {
+        let program = Program::new(vec![print(binary(kind, float(1.0), int(1)))]);
+
+        let output = folders(&["run", "--extensions", program.path()]);
+        assert_eq!(output.status.code(), Some(1), "{kind}");
+        assert!(
+            stderr(&output).contains("bitwise operations only apply to Ints, found Float and Int"),
+            "{kind} : {}",
+            stderr(&output)
+        );
+
+        let output = folders(&["check", "--extensions", program.path()]);
+        assert_eq!(output.status.code(), Some(1), "{kind}");
+    }
+}
+
+#[test]
+fn modulo_only_applies_to_numbers() {
+    for (operand, found) in [
+        (char('a'), "Char and Int"),
+        (string("a"), "String and Int"),
+        (boolean(true), "Bool and Int"),
+    ] {
+        let program = Program::new(vec![print(modulo(operand, int(2)))]);
+        let output = folders(&["run", "--extensions", "--booleans", program.path()]);
+
+        assert_eq!(output.status.code(), Some(1), "{found}");
+        assert!(
+            stderr(&output).contains(&format!(
+                "modulo only applies to Ints and Floats, found {found}"
+            )),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn modulo_by_zero_fails() {
+    let program = Program::new(vec![print(modulo(int(7), int(0)))]);
+    let output = folders(&["run", "--extensions", program.path()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("modulo by zero"),
+        "{}",
+        stderr(&output)
+    );
+}
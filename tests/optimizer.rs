@@ -0,0 +1,125 @@
+//! Programs run with `-O` behave like the ones run without it.
+
+mod common;
+
+use common::*;
+
+/// Standard output and error of a run, with and without optimizations
+fn runs(folder: &str, options: &[&str]) -> [(String, String, Option<i32>); 2] {
+    [false, true].map(|optimize| {
+        let mut args = vec!["run", "--input", "3", "--input", "4"];
+        args.extend(options);
+        if optimize {
+            args.push("-O");
+        }
+        args.push(folder);
+
+        let output = folders(&args);
+        (
+            String::from_utf8(output.stdout.clone()).unwrap(),
+            stderr(&output),
+            output.status.code(),
+        )
+    })
+}
+
+fn assert_same_runs(folder: &str, options: &[&str]) {
+    let [unoptimized, optimized] = runs(folder, options);
+
+    assert_eq!(unoptimized, optimized, "{folder} {options:?}");
+}
+
+#[test]
+fn samples_run_the_same() {
+    for name in SAMPLES {
+        assert_same_runs(&sample(name), &[]);
+    }
+}
+
+#[test]
+fn constants_run_the_same() {
+    let program = Program::new(vec![
+        declare(0, 0),
+        let_(0, add(mul(int(6), int(7)), sub(int(1), int(2)))),
+        print(var(0)),
+        newline(),
+        print(add(string("con"), string("stant"))),
+        newline(),
+        print(div(float(1.0), float(4.0))),
+        newline(),
+        if_(gt(int(1), int(2)), vec![print(string("dead"))]),
+        if_(lt(int(1), int(2)), vec![print(string("alive"))]),
+        while_(gt(int(0), int(1)), vec![print(string("never"))]),
+        while_(
+            lt(var(0), add(int(40), int(5))),
+            vec![let_(0, add(var(0), int(1))), print(var(0))],
+        ),
+    ]);
+
+    assert_same_runs(program.path(), &[]);
+}
+
+#[test]
+fn failing_constants_fail_the_same() {
+    let program = Program::new(vec![
+        print(string("before")),
+        print(add(int(i32::MAX), int(1))),
+    ]);
+
+    assert_same_runs(program.path(), &["--numeric", "checked"]);
+    assert_same_runs(program.path(), &["--numeric", "wrapping"]);
+    assert_same_runs(program.path(), &["--numeric", "saturating"]);
+
+    let [(output, error, status), _] = runs(program.path(), &["--numeric", "checked"]);
+    assert_eq!(output, "before");
+    assert_eq!(status, Some(1));
+    assert!(error.contains("overflow"), "{error}");
+}
+
+#[test]
+fn extension_constants_run_the_same() {
+    let program = Program::new(vec![
+        l(vec![
+            n(0),
+            gt(int(2), int(1)),
+            l(vec![print(string("then"))]),
+            l(vec![print(string("else"))]),
+        ]),
+        print(modulo(int(17), int(5))),
+        print(int_divide(int(17), int(5))),
+    ]);
+
+    assert_same_runs(program.path(), &["--extensions"]);
+}
+
+#[test]
+fn self_modifying_programs_are_not_optimized() {
+    let program = Program::new(vec![
+        mkdir(string("made")),
+        rmdir(string("made")),
+        print(add(int(1), int(2))),
+    ]);
+    let [unoptimized, optimized] = runs(program.path(), &["--extensions", "--no-cache"]);
+
+    assert_eq!(unoptimized.0, "3");
+    assert_eq!(unoptimized.2, Some(0));
+    assert_eq!(optimized.2, Some(1));
+    assert!(
+        optimized
+            .1
+            .contains("mkdir command modifies the program folders, it can not be optimized"),
+        "{}",
+        optimized.1
+    );
+}
+
+#[test]
+fn unreachable_self_modifying_commands_are_optimized() {
+    let program = Program::new(vec![
+        if_(gt(int(1), int(2)), vec![write(string("value"), int(1))]),
+        print(string("done")),
+    ]);
+
+    assert_same_runs(program.path(), &["--extensions"]);
+    assert_eq!(runs(program.path(), &["--extensions"])[1].2, Some(0));
+}
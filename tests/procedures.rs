@@ -0,0 +1,147 @@
+//! Procedures : recursion depth, global variables and calls from expressions.
+
+mod common;
+
+use common::*;
+
+/// Procedure 0 calls itself `depth` times and prints the depth reached
+fn recursion(depth: i32) -> Program {
+    Program::new(vec![
+        define(
+            0,
+            1,
+            vec![
+                if_(
+                    lt(var(0), int(depth)),
+                    vec![call_command(0, vec![add(var(0), int(1))])],
+                ),
+                if_(lt(int(depth - 1), var(0)), vec![print(var(0))]),
+            ],
+        ),
+        call_command(0, vec![int(0)]),
+    ])
+}
+
+/// Procedure 0 returns the factorial of its argument, recursively
+fn factorial() -> Node {
+    define(
+        0,
+        1,
+        vec![
+            if_(lt(var(0), int(2)), vec![return_(int(1))]),
+            return_(mul(var(0), call(0, vec![sub(var(0), int(1))]))),
+        ],
+    )
+}
+
+/// Procedure 0 reads global var_0 and writes global var_1
+fn globals() -> Program {
+    Program::new(vec![
+        define(0, 0, vec![let_(1, add(var(0), int(1)))]),
+        declare(0, 0),
+        declare(0, 1),
+        let_(0, int(41)),
+        let_(1, int(0)),
+        call_command(0, vec![]),
+        print(var(1)),
+    ])
+}
+
+#[test]
+fn recursion_stops_at_max_call_depth() {
+    // the first call is not nested
+    let program = recursion(9);
+    assert_eq!(
+        stdout(&[
+            "run",
+            "--extensions",
+            "--max-call-depth",
+            "10",
+            program.path()
+        ]),
+        "9"
+    );
+
+    let program = recursion(10);
+    let output = folders(&[
+        "run",
+        "--extensions",
+        "--max-call-depth",
+        "10",
+        program.path(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("maximum call depth of 10 reached"));
+}
+
+#[test]
+fn deep_recursion_runs_with_default_depth() {
+    let program = recursion(500);
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "500");
+}
+
+#[test]
+fn procedures_read_and_write_globals() {
+    let program = globals();
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "42");
+    assert_eq!(stdout(&["check", "--extensions", program.path()]), "");
+}
+
+#[test]
+fn procedures_using_globals_are_not_transpiled() {
+    let program = globals();
+    let output = folders(&["transpile", "--extensions", program.path()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("procedures can not use global variable var_0 once transpiled"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn arguments_hide_globals() {
+    // var_0 of the procedure is its argument, not the global var_0
+    let program = Program::new(vec![
+        define(0, 1, vec![print(var(0))]),
+        declare(0, 0),
+        let_(0, int(1)),
+        call_command(0, vec![int(2)]),
+        print(var(0)),
+    ]);
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "21");
+    assert!(stdout(&["transpile", "--extensions", program.path()]).contains("fn proc_0"));
+}
+
+#[test]
+fn call_expressions_run_and_build() {
+    let program = Program::new(vec![
+        factorial(),
+        print(call(0, vec![int(5)])),
+        newline(),
+        print(add(call(0, vec![int(3)]), call(0, vec![int(4)]))),
+    ]);
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), "120\n30");
+
+    let dir = TempDir::new();
+    let executable = dir.join("factorial");
+    stdout(&["build", "--extensions", "-o", &executable, program.path()]);
+
+    let output = std::process::Command::new(&executable).output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "120\n30");
+}
+
+#[test]
+fn call_expressions_need_a_returned_value() {
+    let program = Program::new(vec![
+        define(0, 0, vec![print(string("no value"))]),
+        print(call(0, vec![])),
+    ]);
+
+    let output = folders(&["check", "--extensions", program.path()]);
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,100 @@
+//! `--profile` on a recursive procedure and nested loops.
+
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+
+/// Procedure 0 calls itself until its argument is 5, then two nested loops run 3 and 4 times
+fn program() -> Program {
+    Program::new(vec![
+        define(
+            0,
+            1,
+            vec![if_(
+                lt(var(0), int(5)),
+                vec![call_command(0, vec![add(var(0), int(1))])],
+            )],
+        ),
+        call_command(0, vec![int(0)]),
+        declare(0, 0),
+        let_(0, int(0)),
+        while_(
+            lt(var(0), int(3)),
+            vec![
+                let_(1, int(0)),
+                while_(lt(var(1), int(4)), vec![let_(1, add(var(1), int(1)))]),
+                let_(0, add(var(0), int(1))),
+            ],
+        ),
+    ])
+}
+
+/// Counts of a report section, indexed by folder
+fn section(report: &str, title: &str) -> HashMap<String, usize> {
+    report
+        .split(&format!("{title} :\n"))
+        .nth(1)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            let count = fields.next().unwrap().parse().unwrap();
+            (fields.last().unwrap().to_owned(), count)
+        })
+        .collect()
+}
+
+#[test]
+fn recursion_and_nested_loops() {
+    let program = program();
+    let dir = TempDir::new();
+    let folded = dir.join("profile.folded");
+
+    let output = folders(&[
+        "run",
+        "--extensions",
+        "--profile",
+        "--profile-folded",
+        &folded,
+        program.path(),
+    ]);
+    assert!(output.status.success());
+    let report = stderr(&output);
+    let folder = |path: &str| format!("{}/{path}", program.path());
+
+    assert!(report.starts_with(&format!("profile of {} : ", program.path())));
+
+    let hits = section(&report, "folders");
+    assert_eq!(hits[&folder("001")], 1);
+    // the recursive call runs once per level
+    assert_eq!(hits[&folder("000/003/000/002/000")], 5);
+    assert_eq!(hits[&folder("004")], 1);
+    assert_eq!(hits[&folder("004/002/001")], 3);
+    assert_eq!(hits[&folder("004/002/001/002/000")], 12);
+
+    let iterations = section(&report, "hottest loops");
+    assert_eq!(iterations[&folder("004")], 3);
+    assert_eq!(iterations[&folder("004/002/001")], 12);
+    assert_eq!(iterations.len(), 2);
+
+    let folded = std::fs::read_to_string(&folded).unwrap();
+    for line in folded.lines() {
+        let (stack, micros) = line.rsplit_once(' ').unwrap();
+        assert!(micros.parse::<u128>().is_ok(), "{line}");
+        assert!(stack
+            .split(';')
+            .all(|frame| frame.starts_with(program.path())));
+    }
+
+    // stacks hold one frame per nested call
+    let recursive_call = folder("000/003/000/002/000");
+    let deepest = folded
+        .lines()
+        .map(|line| line.matches(&format!("{recursive_call};")).count())
+        .max()
+        .unwrap();
+    assert_eq!(deepest, 5);
+}
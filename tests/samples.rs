@@ -0,0 +1,160 @@
+//! Every sample goes through each subcommand : run, check, transpile, build,
+//! disasm, graph, lint, fmt and diff.
+
+mod common;
+
+use common::*;
+use std::process::Command;
+
+const INPUT: &str = "3\n4\n";
+
+/// Lines read as strings by the interpreter too
+const WORDS: &str = "foo\nbar\n";
+
+fn run(name: &str, input: &str) -> String {
+    let output = folders_with_stdin(&["run", &sample(name)], input);
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn samples_pass_check() {
+    for name in SAMPLES {
+        assert_eq!(stdout(&["check", &sample(name)]), "", "{name}");
+    }
+}
+
+#[test]
+fn samples_run() {
+    assert!(run("99Bottles", INPUT).starts_with("99 bottles of beer on the wall, "));
+    assert_eq!(run("AddTwoNumbersOrStrings", INPUT), "7");
+    assert_eq!(run("HelloWorld", INPUT), "Hello, World!");
+    assert_eq!(run("SimpleEcho", INPUT), "3");
+    assert_eq!(
+        stdout(&["run", "--input", "3", "--input", "4", &sample("SimpleEcho")]),
+        "3"
+    );
+}
+
+/// Transpiled programs read undeclared variables as strings
+#[test]
+fn built_samples_print_what_they_print_when_run() {
+    let dir = TempDir::new();
+
+    for name in SAMPLES {
+        let executable = dir.join(name);
+        stdout(&["build", "-o", &executable, &sample(name)]);
+
+        let output = Command::new(&executable)
+            .stdin(std::fs::File::open(input_file(&dir)).unwrap())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{name}");
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            run(name, WORDS),
+            "{name}"
+        );
+    }
+}
+
+fn input_file(dir: &TempDir) -> String {
+    let path = dir.join("input");
+    std::fs::write(&path, WORDS).unwrap();
+
+    path
+}
+
+#[test]
+fn samples_transpile_to_stdout_or_to_a_file() {
+    let dir = TempDir::new();
+
+    for name in SAMPLES {
+        for target in ["rust", "wat"] {
+            let source = stdout(&["transpile", "--target", target, &sample(name)]);
+            assert!(!source.is_empty(), "{name} {target}");
+
+            let path = dir.join(&format!("{name}.{target}"));
+            assert_eq!(
+                stdout(&["transpile", "--target", target, "-o", &path, &sample(name)]),
+                ""
+            );
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), source, "{name}");
+        }
+
+        let wat = stdout(&["transpile", "--target", "wat", &sample(name)]);
+        assert!(wat::parse_str(&wat).is_ok(), "{name}");
+    }
+}
+
+#[test]
+fn samples_disassemble() {
+    assert_eq!(
+        stdout(&["disasm", &sample("AddTwoNumbersOrStrings")]),
+        "input var_0\ninput var_1\nprint var_0 + var_1\n"
+    );
+    assert_eq!(
+        stdout(&["disasm", &sample("HelloWorld")]),
+        "print \"Hello, World!\"\n"
+    );
+
+    for name in SAMPLES {
+        let listing = stdout(&["disasm", &sample(name)]);
+        let with_folders = stdout(&["disasm", "--folders", &sample(name)]);
+
+        assert_eq!(
+            listing.lines().count(),
+            with_folders.lines().count(),
+            "{name}"
+        );
+        for line in with_folders.lines().filter(|line| line.contains(" # ")) {
+            let folder = line.rsplit_once(" # ").unwrap().1;
+            assert!(std::path::Path::new(folder).is_dir(), "{name} : {line}");
+        }
+    }
+}
+
+#[test]
+fn samples_render_as_graphs() {
+    for name in SAMPLES {
+        let dot = stdout(&["graph", &sample(name)]);
+
+        assert!(dot.starts_with("digraph program {"), "{name}");
+        assert!(dot.trim_end().ends_with('}'), "{name}");
+    }
+}
+
+#[test]
+fn samples_are_linted() {
+    for name in SAMPLES {
+        let warnings = stdout(&["lint", &sample(name)]);
+
+        assert!(
+            warnings.contains("'.keep' is not a folder and is ignored"),
+            "{name}"
+        );
+        assert!(warnings.lines().all(|line| line.starts_with("warning: ")));
+    }
+}
+
+#[test]
+fn samples_are_not_formatted() {
+    for name in SAMPLES {
+        assert_eq!(
+            folders(&["fmt", "--check", &sample(name)]).status.code(),
+            Some(1),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn samples_equal_themselves() {
+    for name in SAMPLES {
+        assert_eq!(
+            stdout(&["diff", &sample(name), &sample(name)]),
+            "",
+            "{name}"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Transpiled programs declare their variables where the interpreter creates them.
+
+mod common;
+
+use common::*;
+
+/// Output of `program` run and built, which must be the same
+fn run_and_build(program: &Program) -> String {
+    let output = stdout(&["run", program.path()]);
+    assert_eq!(built(&[], program.path(), ""), output);
+    assert_eq!(stdout(&["check", program.path()]), "");
+
+    output
+}
+
+#[test]
+fn loop_bodies_assigning_a_variable_first_declare_it() {
+    let program = Program::new(vec![
+        let_(0, int(0)),
+        while_(
+            lt(var(0), int(3)),
+            vec![
+                let_(1, mul(var(0), int(10))),
+                print(var(1)),
+                print(char(' ')),
+                let_(0, add(var(0), int(1))),
+            ],
+        ),
+    ]);
+
+    assert_eq!(run_and_build(&program), "0 10 20 ");
+}
+
+#[test]
+fn bodies_assign_outer_variables() {
+    let program = Program::new(vec![
+        let_(0, int(1)),
+        if_(gt(var(0), int(0)), vec![let_(0, int(2))]),
+        let_(1, int(0)),
+        while_(
+            lt(var(1), int(3)),
+            vec![let_(0, add(var(0), int(1))), let_(1, add(var(1), int(1)))],
+        ),
+        print(var(0)),
+    ]);
+
+    assert_eq!(run_and_build(&program), "5");
+}
+
+#[test]
+fn variables_of_sibling_bodies_do_not_clash() {
+    let program = Program::new(vec![
+        if_(
+            int(1),
+            vec![let_(0, string("first")), print(var(0)), print(char(' '))],
+        ),
+        if_(
+            int(1),
+            vec![let_(0, int(2)), print(var(0)), print(char(' '))],
+        ),
+        let_(0, char('c')),
+        print(var(0)),
+    ]);
+
+    assert_eq!(run_and_build(&program), "first 2 c");
+}
+
+#[test]
+fn nested_bodies_see_the_variables_of_their_parents() {
+    let program = Program::new(vec![
+        let_(0, int(0)),
+        while_(
+            lt(var(0), int(2)),
+            vec![
+                let_(1, int(100)),
+                if_(
+                    int(1),
+                    vec![
+                        let_(1, add(var(1), var(0))),
+                        print(var(1)),
+                        print(char(' ')),
+                    ],
+                ),
+                let_(0, add(var(0), int(1))),
+            ],
+        ),
+    ]);
+
+    assert_eq!(run_and_build(&program), "100 101 ");
+}
+
+#[test]
+fn variables_of_a_body_are_gone_after_it() {
+    let program = Program::new(vec![if_(int(1), vec![let_(0, int(1))]), print(var(0))]);
+
+    assert!(!folders(&["run", program.path()]).status.success());
+    assert!(!folders(&["check", program.path()]).status.success());
+    assert!(!folders(&["transpile", program.path()]).status.success());
+}
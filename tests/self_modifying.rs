@@ -0,0 +1,121 @@
+//! `write`, `mkdir` and `rmdir` change the program folders while it runs.
+
+mod common;
+
+use common::*;
+use std::path::Path;
+
+fn run(program: &Program, options: &[&str]) -> std::process::Output {
+    folders(&[&["run", "--extensions"], options, &[program.path()]].concat())
+}
+
+/// The first command writes the expression of the print command after it
+fn writing() -> Program {
+    Program::new(vec![write(string("001/001"), int(7)), l(vec![n(4)])])
+}
+
+#[test]
+fn written_literals_are_run() {
+    for options in [&[][..], &["--no-cache"]] {
+        let program = writing();
+        let output = run(&program, options);
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(output.stdout, b"7");
+        assert_eq!(
+            stdout(&["disasm", "--extensions", program.path()]),
+            "write \"001/001\", 7\nprint 7\n"
+        );
+    }
+}
+
+#[test]
+fn every_value_type_is_written() {
+    for (value, printed) in [(float(2.5), "2.5"), (string("hi"), "hi"), (char('x'), "x")] {
+        let program = Program::new(vec![write(string("001/001"), value), l(vec![n(4)])]);
+
+        assert_eq!(stdout(&["run", "--extensions", program.path()]), printed);
+    }
+}
+
+#[test]
+fn folders_are_created_and_removed() {
+    let program = Program::new(vec![
+        mkdir(string("data")),
+        mkdir(string("data/nested")),
+        mkdir(string("data/nested/deeper")),
+        rmdir(string("data/nested")),
+        mkdir(string("data/kept")),
+    ]);
+
+    let output = run(&program, &[]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let data = Path::new(program.path()).join("data");
+    assert!(data.join("kept").is_dir());
+    assert!(!data.join("nested").exists());
+}
+
+#[test]
+fn paths_stay_inside_of_the_program() {
+    for path in ["../outside", "/tmp/outside", "001/../../outside"] {
+        let program = Program::new(vec![mkdir(string(path))]);
+        let output = run(&program, &[]);
+
+        assert!(!output.status.success(), "{path}");
+        assert!(
+            stderr(&output).contains("only paths relative to the program folder"),
+            "{path} : {}",
+            stderr(&output)
+        );
+    }
+
+    let program = Program::new(vec![rmdir(string("."))]);
+    let output = run(&program, &[]);
+    assert!(stderr(&output).contains("the program folder can not be removed"));
+    assert!(Path::new(program.path()).is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_links_do_not_lead_outside() {
+    let outside = TempDir::new();
+    let program = Program::new(vec![mkdir(string("link/created"))]);
+    std::os::unix::fs::symlink(outside.path(), Path::new(program.path()).join("link")).unwrap();
+
+    let output = run(&program, &[]);
+
+    assert!(stderr(&output).contains("path leads outside of the program folder"));
+    assert!(!outside.path().join("created").exists());
+}
+
+#[test]
+fn extension_commands_need_extensions() {
+    let program = writing();
+    let output = folders(&["run", program.path()]);
+
+    assert!(stderr(&output).contains("'write' command is an extension, run with --extensions"));
+    assert!(!Path::new(program.path()).join("001/001").exists());
+}
+
+#[test]
+fn extension_commands_are_not_transpiled() {
+    let program = Program::new(vec![write(string("002"), int(7)), print(int(1))]);
+
+    for target in ["rust", "wat"] {
+        let output = folders(&[
+            "transpile",
+            "--extensions",
+            "--target",
+            target,
+            program.path(),
+        ]);
+
+        assert!(!output.status.success(), "{target}");
+        assert!(
+            stderr(&output).contains("modifies the program folders"),
+            "{target} : {}",
+            stderr(&output)
+        );
+    }
+}
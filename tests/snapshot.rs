@@ -0,0 +1,145 @@
+//! Snapshots taken on SIGINT, including while the program waits for input,
+//! and resumed from any path to the program.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::*;
+use std::{
+    io::Read,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Program printing "ready", then the line it reads
+fn echo() -> Program {
+    Program::new(vec![print(string("ready")), input(0), print(var(0))])
+}
+
+/// Start `run --snapshot` with stdin left open and empty
+fn start(program: &str, snapshot: &str, dir: &TempDir) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_folders"))
+        .args(["run", "--snapshot", snapshot, program])
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+fn wait_for_output(child: &mut Child, expected: &str) {
+    let stdout = child.stdout.as_mut().unwrap();
+    let mut output = vec![0; expected.len()];
+    stdout.read_exact(&mut output).unwrap();
+
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
+
+fn interrupt(child: &Child) {
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Exit code of `child`, killed if it is still running after a few seconds
+fn exit_code(child: &mut Child) -> Option<i32> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status.code();
+        }
+        if start.elapsed() > Duration::from_secs(10) {
+            child.kill().unwrap();
+            panic!("the program did not stop");
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn interrupt_while_waiting_for_input_saves_snapshot() {
+    let program = echo();
+    let dir = TempDir::new();
+    let snapshot = dir.join("state.json");
+
+    let mut child = start(program.path(), &snapshot, &dir);
+    wait_for_output(&mut child, "ready");
+    // let it block on stdin
+    std::thread::sleep(Duration::from_millis(300));
+    interrupt(&child);
+
+    assert_eq!(exit_code(&mut child), Some(130));
+    assert!(std::path::Path::new(&snapshot).exists());
+
+    // the 'input' command runs again
+    let output = folders_with_stdin(&["run", "--resume", &snapshot, program.path()], "hello\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "hello");
+}
+
+#[test]
+fn input_read_while_waiting_is_not_lost() {
+    let program = Program::new(vec![input(0), print(var(0)), input(1), print(var(1))]);
+    let dir = TempDir::new();
+    let snapshot = dir.join("state.json");
+
+    let mut child = start(program.path(), &snapshot, &dir);
+    {
+        use std::io::Write;
+        let stdin = child.stdin.as_mut().unwrap();
+        stdin.write_all(b"first\n").unwrap();
+        stdin.flush().unwrap();
+    }
+    wait_for_output(&mut child, "first");
+    interrupt(&child);
+    assert_eq!(exit_code(&mut child), Some(130));
+
+    let output = folders_with_stdin(&["run", "--resume", &snapshot, program.path()], "second\n");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "second");
+}
+
+#[test]
+fn resume_accepts_other_paths_to_the_program() {
+    let program = echo();
+    let dir = TempDir::new();
+    let snapshot = dir.join("state.json");
+
+    // started from a path relative to the directory of the snapshot
+    let relative = pathdiff(program.path(), dir.path());
+    let mut child = start(&relative, &snapshot, &dir);
+    wait_for_output(&mut child, "ready");
+    interrupt(&child);
+    assert_eq!(exit_code(&mut child), Some(130));
+
+    let absolute = format!("{}/.", program.path());
+    let output = folders_with_stdin(&["run", "--resume", &snapshot, &absolute], "again\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "again");
+}
+
+#[test]
+fn resume_rejects_other_programs() {
+    let program = echo();
+    let other = echo();
+    let dir = TempDir::new();
+    let snapshot = dir.join("state.json");
+
+    let mut child = start(program.path(), &snapshot, &dir);
+    wait_for_output(&mut child, "ready");
+    interrupt(&child);
+    assert_eq!(exit_code(&mut child), Some(130));
+
+    let output = folders_with_stdin(&["run", "--resume", &snapshot, other.path()], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("snapshot taken while running"));
+}
+
+/// `path` relative to `base`, both being absolute
+fn pathdiff(path: &str, base: &std::path::Path) -> String {
+    let depth = base.components().count() - 1;
+    format!("{}{}", "../".repeat(depth), path.trim_start_matches('/'))
+}
@@ -0,0 +1,105 @@
+//! Strings are counted, indexed and cut in chars, chars concatenate into strings.
+
+mod common;
+
+use common::*;
+
+fn index_get(sequence: Node, index: Node) -> Node {
+    l(vec![n(20), sequence, index])
+}
+
+fn length(sequence: Node) -> Node {
+    l(vec![n(22), sequence])
+}
+
+fn substring(value: Node, start: Node, end: Node) -> Node {
+    l(vec![n(25), value, start, end])
+}
+
+/// Program printing every expression on its own line, and the expected output
+fn program(cases: Vec<(Node, &str)>) -> (Program, String) {
+    let mut commands = vec![];
+    let mut expected = String::new();
+    for (expression, printed) in cases {
+        commands.extend([print(expression), newline()]);
+        expected += printed;
+        expected += "\n";
+    }
+
+    (Program::new(commands), expected)
+}
+
+fn operations() -> Vec<(Node, &'static str)> {
+    vec![
+        (length(string("hello")), "5"),
+        (length(string("")), "0"),
+        (index_get(string("hello"), int(1)), "e"),
+        (substring(string("hello"), int(1), int(4)), "ell"),
+        (substring(string("hello"), int(2), int(2)), ""),
+        (add(string("ab"), char('c')), "abc"),
+        (add(char('a'), char('b')), "ab"),
+    ]
+}
+
+#[test]
+fn string_operations_run_and_build() {
+    let (program, expected) = program(operations());
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), expected);
+    assert_eq!(built(&["--extensions"], program.path(), ""), expected);
+}
+
+#[test]
+fn ascii_string_operations_are_webassembly() {
+    let (program, expected) = program(operations());
+    let text = stdout(&[
+        "transpile",
+        "--extensions",
+        "--target",
+        "wat",
+        program.path(),
+    ]);
+
+    let run = run_wat(&text, &[]);
+    assert!(!run.trapped);
+    assert_eq!(run.output, expected);
+}
+
+#[test]
+fn strings_are_counted_in_chars() {
+    let (program, expected) = program(vec![
+        (length(string("été")), "3"),
+        (index_get(string("été"), int(1)), "t"),
+        (substring(string("été"), int(2), int(3)), "é"),
+    ]);
+
+    assert_eq!(stdout(&["run", "--extensions", program.path()]), expected);
+    assert_eq!(built(&["--extensions"], program.path(), ""), expected);
+}
+
+#[test]
+fn chars_concatenate_without_extensions() {
+    let (program, expected) = program(vec![
+        (add(char('a'), char('b')), "ab"),
+        (add(string("a"), char('b')), "ab"),
+    ]);
+
+    assert_eq!(stdout(&["run", program.path()]), expected);
+    assert_eq!(built(&[], program.path(), ""), expected);
+}
+
+#[test]
+fn indexes_out_of_the_string_fail() {
+    for expression in [
+        index_get(string("abc"), int(3)),
+        substring(string("abc"), int(2), int(4)),
+        substring(string("abc"), int(2), int(1)),
+        substring(string("abc"), int(-1), int(1)),
+    ] {
+        let program = Program::new(vec![print(expression)]);
+        let output = folders(&["run", "--extensions", program.path()]);
+
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+}
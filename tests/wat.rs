@@ -0,0 +1,81 @@
+//! Programs transpiled with `--target wat` are valid modules printing what the interpreter prints.
+
+mod common;
+
+use common::*;
+
+const INPUT: [&str; 2] = ["foo", "bar"];
+
+/// Output of the interpreter, reading the same input as the WebAssembly imports
+fn interpreted(folder: &str) -> String {
+    let stdin: String = INPUT.iter().map(|line| format!("{line}\n")).collect();
+    let output = folders_with_stdin(&["run", folder], &stdin);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn transpiled(folder: &str) -> WasmRun {
+    run_wat(&stdout(&["transpile", "--target", "wat", folder]), &INPUT)
+}
+
+fn assert_same_output(folder: &str) {
+    let run = transpiled(folder);
+
+    assert!(!run.trapped, "{folder} trapped");
+    assert_eq!(run.output, interpreted(folder), "{folder}");
+}
+
+#[test]
+fn samples_print_as_interpreted() {
+    for name in SAMPLES {
+        assert_same_output(&sample(name));
+    }
+}
+
+#[test]
+fn string_input_is_concatenated() {
+    let run = transpiled(&sample("AddTwoNumbersOrStrings"));
+
+    assert_eq!(run.output, "foobar");
+}
+
+#[test]
+fn loops_and_floats_print_as_interpreted() {
+    let program = Program::new(vec![
+        declare(0, 0),
+        declare(1, 1),
+        let_(0, int(0)),
+        let_(1, float(0.5)),
+        while_(
+            lt(var(0), int(5)),
+            vec![
+                let_(0, add(var(0), int(1))),
+                let_(1, mul(var(1), float(3.0))),
+                print(var(0)),
+                print(char(' ')),
+                print(var(1)),
+                newline(),
+            ],
+        ),
+        print(add(string("done "), string("here"))),
+    ]);
+
+    assert_same_output(program.path());
+}
+
+#[test]
+fn procedures_are_rejected() {
+    let program = Program::new(vec![define(0, 0, vec![return_(int(1))])]);
+
+    let output = folders(&[
+        "transpile",
+        "--target",
+        "wat",
+        "--extensions",
+        program.path(),
+    ]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("wat target does not support procedures"));
+}
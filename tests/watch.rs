@@ -0,0 +1,157 @@
+//! `run --watch` runs a program again with the same input each time its folders change.
+
+mod common;
+
+use std::{
+    io::{BufReader, Read},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+use common::*;
+
+/// Running `run --watch`, killed on drop
+struct Watch {
+    child: Child,
+    stdout: Receiver<u8>,
+    stderr: Receiver<u8>,
+}
+
+impl Watch {
+    fn new(args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_folders"))
+            .args([&["run", "--watch", "-v"], args].concat())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let stdout = bytes(child.stdout.take().unwrap());
+        let stderr = bytes(child.stderr.take().unwrap());
+
+        Watch {
+            child,
+            stdout,
+            stderr,
+        }
+    }
+
+    /// Output read until it ends with `expected`, panics after a few seconds
+    fn until(receiver: &Receiver<u8>, output: &mut String, expected: &str) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+
+        while !output.ends_with(expected) {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(left) {
+                Ok(byte) => output.push(byte as char),
+                Err(_) => panic!("expected {expected:?}, got {output:?}"),
+            }
+        }
+    }
+}
+
+impl Watch {
+    /// Wait until the folders of `folder` are watched, changes made before are missed
+    fn watching(&self, stderr: &mut String, folder: &str) {
+        Watch::until(
+            &self.stderr,
+            stderr,
+            &format!("waiting for changes in {folder}\n"),
+        );
+        // folders are listed right after the message
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Bytes of `stream`, read on their own thread
+fn bytes(stream: impl Read + Send + 'static) -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        for byte in BufReader::new(stream).bytes() {
+            let Ok(byte) = byte else { break };
+            if sender.send(byte).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Reads a line and prints it
+fn echo() -> Program {
+    Program::new(vec![input(0), print(var(0)), newline()])
+}
+
+/// Add `command` after the commands of `program`
+fn append(program: &Program, command: Node) {
+    let count = std::fs::read_dir(program.path()).unwrap().count();
+
+    command.create(&Path::new(program.path()).join(format!("{count:03}")));
+}
+
+#[test]
+fn changed_programs_run_again_with_the_same_input() {
+    let program = echo();
+    let watch = Watch::new(&[
+        "--input-policy",
+        "string",
+        "--input",
+        "hello",
+        program.path(),
+    ]);
+    let mut stdout = String::new();
+    Watch::until(&watch.stdout, &mut stdout, "hello\n");
+    watch.watching(&mut String::new(), program.path());
+
+    append(&program, print(char('!')));
+
+    Watch::until(&watch.stdout, &mut stdout, "hello\nhello\n!");
+}
+
+#[test]
+fn listing_changes_are_shown_as_a_diff() {
+    let program = echo();
+    let watch = Watch::new(&["--input", "a", program.path()]);
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    Watch::until(&watch.stdout, &mut stdout, "a\n");
+    watch.watching(&mut stderr, program.path());
+
+    append(&program, print(int(7)));
+    Watch::until(&watch.stdout, &mut stdout, "a\n7");
+
+    Watch::until(
+        &watch.stderr,
+        &mut stderr,
+        &format!("+++ {}\n", program.path()),
+    );
+    assert!(stderr.contains(&format!("--- {} (last run)\n", program.path())));
+
+    Watch::until(&watch.stderr, &mut stderr, "+print 7\n");
+}
+
+#[test]
+fn errors_do_not_stop_watching() {
+    let program = Program::new(vec![l(vec![n(3)]), print(string("a"))]);
+    let watch = Watch::new(&[program.path()]);
+    let mut stderr = String::new();
+    watch.watching(&mut stderr, program.path());
+    assert!(stderr.starts_with("Error: "), "{stderr}");
+
+    std::fs::remove_dir_all(Path::new(program.path()).join("000")).unwrap();
+
+    let mut stdout = String::new();
+    Watch::until(&watch.stdout, &mut stdout, "a");
+}